@@ -1,4 +1,5 @@
 use crate::config::FrumConfig;
+use crate::lock::lock_aliases;
 use crate::symlink::{create_symlink_dir, remove_symlink_dir};
 use crate::version::Version;
 use std::path::PathBuf;
@@ -8,6 +9,26 @@ pub fn create_alias(
     common_name: &str,
     version: &Version,
 ) -> std::io::Result<()> {
+    let _lock = lock_aliases(config)?;
+    replace_alias(config, common_name, version)
+}
+
+/// Creates the alias only if it doesn't point anywhere yet. Returns whether
+/// the alias was created.
+pub fn create_alias_if_missing(
+    config: &FrumConfig,
+    common_name: &str,
+    version: &Version,
+) -> std::io::Result<bool> {
+    let _lock = lock_aliases(config)?;
+    if config.aliases_dir().join(common_name).exists() {
+        return Ok(false);
+    }
+    replace_alias(config, common_name, version)?;
+    Ok(true)
+}
+
+fn replace_alias(config: &FrumConfig, common_name: &str, version: &Version) -> std::io::Result<()> {
     let aliases_dir = config.aliases_dir();
     std::fs::create_dir_all(&aliases_dir)?;
 
@@ -1,11 +1,18 @@
 use std::error::Error as StdError;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 pub enum Error {
     IoError(std::io::Error),
     ZipError(zip::result::ZipError),
     HttpError(reqwest::Error),
+    UnknownFormat,
+    AbsolutePath { path: PathBuf },
+    PathTraversal { path: PathBuf },
+    LinkOutsideTarget { path: PathBuf, target: PathBuf },
+    UnsupportedEntry { path: PathBuf, entry_type: String },
+    EntryTooLarge { path: PathBuf, size: u64 },
+    ArchiveTooLarge { size: u64 },
 }
 
 impl std::fmt::Display for Error {
@@ -14,6 +21,40 @@ impl std::fmt::Display for Error {
             Self::IoError(x) => x.fmt(f),
             Self::ZipError(x) => x.fmt(f),
             Self::HttpError(x) => x.fmt(f),
+            Self::UnknownFormat => write!(f, "Unknown archive format"),
+            Self::AbsolutePath { path } => {
+                write!(
+                    f,
+                    "The archive contains an absolute path: {}",
+                    path.display()
+                )
+            }
+            Self::PathTraversal { path } => write!(
+                f,
+                "The archive contains a path outside of the target directory: {}",
+                path.display()
+            ),
+            Self::LinkOutsideTarget { path, target } => write!(
+                f,
+                "The archive contains a link pointing outside of the target directory: {} -> {}",
+                path.display(),
+                target.display()
+            ),
+            Self::UnsupportedEntry { path, entry_type } => write!(
+                f,
+                "The archive contains an unsupported entry ({}): {}",
+                entry_type,
+                path.display()
+            ),
+            Self::EntryTooLarge { path, size } => write!(
+                f,
+                "The archive contains a file that is too large ({} bytes): {}",
+                size,
+                path.display()
+            ),
+            Self::ArchiveTooLarge { size } => {
+                write!(f, "The archive is too large to extract ({} bytes)", size)
+            }
         }
     }
 }
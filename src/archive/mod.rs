@@ -1,3 +1,118 @@
 pub mod extract;
+pub mod tar_bz2;
+pub mod tar_gz;
 pub mod tar_xz;
+pub mod unpack;
 pub mod zip;
+
+use extract::{Error, Extract};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    TarXz,
+    TarGz,
+    TarBz2,
+    Zip,
+}
+
+impl Format {
+    pub const ALL: &'static [Self] = &[Self::TarXz, Self::TarGz, Self::TarBz2, Self::Zip];
+
+    /// Formats that can be installed on this platform, in order of preference.
+    #[cfg(unix)]
+    pub const SUPPORTED: &'static [Self] = &[Self::TarXz, Self::TarGz, Self::TarBz2];
+
+    #[cfg(windows)]
+    pub const SUPPORTED: &'static [Self] = &[Self::Zip];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::TarXz => "tar.xz",
+            Self::TarGz => "tar.gz",
+            Self::TarBz2 => "tar.bz2",
+            Self::Zip => "zip",
+        }
+    }
+
+    /// Detects the format from the magic bytes at the beginning of a file.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::TarXz)
+        } else if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::TarGz)
+        } else if header.starts_with(b"BZh") {
+            Some(Self::TarBz2)
+        } else if header.starts_with(&[b'P', b'K', 0x03, 0x04]) {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extracts an archive of any supported format into `path`.
+pub fn extract_into<R: Read + Seek, P: AsRef<Path>>(mut reader: R, path: P) -> Result<(), Error> {
+    let mut header = [0; 6];
+    let len = reader.read(&mut header)?;
+    reader.seek(SeekFrom::Start(0))?;
+    match Format::detect(&header[..len]) {
+        Some(Format::TarXz) => tar_xz::TarXz::new(reader).extract_into(path),
+        Some(Format::TarGz) => tar_gz::TarGz::new(reader).extract_into(path),
+        Some(Format::TarBz2) => tar_bz2::TarBz2::new(reader).extract_into(path),
+        Some(Format::Zip) => zip::Zip::new(reader).extract_into(path),
+        None => Err(Error::UnknownFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn tarball() -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "ruby-2.7.0/configure", &b"#!/sh"[..])
+            .unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_extract_detected_formats() {
+        let tarball = tarball();
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gz.write_all(&tarball).unwrap();
+        let mut bz2 = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+        bz2.write_all(&tarball).unwrap();
+        let mut xz = xz2::write::XzEncoder::new(vec![], 6);
+        xz.write_all(&tarball).unwrap();
+
+        for (format, archive) in [
+            (Format::TarGz, gz.finish().unwrap()),
+            (Format::TarBz2, bz2.finish().unwrap()),
+            (Format::TarXz, xz.finish().unwrap()),
+        ] {
+            assert_eq!(Format::detect(&archive), Some(format));
+            let temp_dir = tempfile::tempdir().unwrap();
+            extract_into(Cursor::new(archive), &temp_dir).unwrap();
+            assert!(temp_dir
+                .path()
+                .join("ruby-2.7.0")
+                .join("configure")
+                .exists());
+        }
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = extract_into(Cursor::new(b"<html></html>".to_vec()), &temp_dir);
+        assert!(matches!(result, Err(Error::UnknownFormat)));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::archive::extract::Error;
+use log::debug;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, EntryType};
+
+/// The largest single file we expect in a Ruby source archive.
+pub const MAX_ENTRY_SIZE: u64 = 1024 * 1024 * 1024;
+/// The largest total size we are willing to unpack from one archive.
+const MAX_TOTAL_SIZE: u64 = 8 * 1024 * 1024 * 1024;
+
+/// Unpacks a tarball into `dst`, rejecting entries that would end up outside of it,
+/// special files and entries with absurd sizes.
+pub fn unpack_tar<R: Read, P: AsRef<Path>>(mut archive: Archive<R>, dst: P) -> Result<(), Error> {
+    let dst = dst.as_ref();
+    let mut total_size: u64 = 0;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let path = normalize_entry_path(&path)?;
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        let entry_type = entry.header().entry_type();
+        match entry_type {
+            EntryType::Regular
+            | EntryType::Continuous
+            | EntryType::Directory
+            | EntryType::Symlink => {}
+            EntryType::Link => {
+                let target = link_name(&entry, &path)?;
+                normalize_entry_path(&target).map_err(|_| Error::LinkOutsideTarget {
+                    path: path.clone(),
+                    target,
+                })?;
+            }
+            _ => {
+                return Err(Error::UnsupportedEntry {
+                    path,
+                    entry_type: format!("{:?}", entry_type),
+                })
+            }
+        }
+
+        let size = entry.header().size()?;
+        if size > MAX_ENTRY_SIZE {
+            return Err(Error::EntryTooLarge { path, size });
+        }
+        total_size = total_size.saturating_add(size);
+        if total_size > MAX_TOTAL_SIZE {
+            return Err(Error::ArchiveTooLarge { size: total_size });
+        }
+
+        debug!("unpack {}", path.display());
+        entry.unpack_in(dst)?;
+
+        if entry_type == EntryType::Symlink {
+            let target = link_name(&entry, &path)?;
+            if let Err(err) = validate_symlink(dst, &path, &target) {
+                std::fs::remove_file(dst.join(&path))?;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the path of an entry relative to the destination directory.
+fn normalize_entry_path(path: &Path) -> Result<PathBuf, Error> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                return Err(Error::AbsolutePath {
+                    path: path.to_path_buf(),
+                })
+            }
+            Component::ParentDir => {
+                return Err(Error::PathTraversal {
+                    path: path.to_path_buf(),
+                })
+            }
+            Component::CurDir => {}
+            Component::Normal(part) => normalized.push(part),
+        }
+    }
+    Ok(normalized)
+}
+
+fn link_name<R: Read>(entry: &tar::Entry<R>, path: &Path) -> Result<PathBuf, Error> {
+    entry
+        .link_name()?
+        .map(|target| target.into_owned())
+        .ok_or_else(|| Error::UnsupportedEntry {
+            path: path.to_path_buf(),
+            entry_type: "link without a target".to_string(),
+        })
+}
+
+/// Checks that the unpacked symlink at `path` pointing to `target` stays inside `dst`.
+/// The link's directory is resolved on disk, since earlier symlinks may have moved it.
+fn validate_symlink(dst: &Path, path: &Path, target: &Path) -> Result<(), Error> {
+    let escapes = || Error::LinkOutsideTarget {
+        path: path.to_path_buf(),
+        target: target.to_path_buf(),
+    };
+    let dst = dst.canonicalize()?;
+    let link = dst.join(path);
+    let link_dir = link.parent().ok_or_else(escapes)?.canonicalize()?;
+    let mut depth = link_dir
+        .strip_prefix(&dst)
+        .map_err(|_| escapes())?
+        .components()
+        .count();
+    for component in target.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return Err(escapes()),
+            Component::ParentDir => depth = depth.checked_sub(1).ok_or_else(escapes)?,
+            Component::CurDir => {}
+            Component::Normal(_) => depth += 1,
+        }
+    }
+    match link.canonicalize() {
+        Ok(resolved) if !resolved.starts_with(&dst) => Err(escapes()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    struct RawEntry<'a> {
+        path: &'a str,
+        entry_type: EntryType,
+        link_name: &'a str,
+        size: u64,
+    }
+
+    impl<'a> RawEntry<'a> {
+        fn file(path: &'a str) -> Self {
+            Self {
+                path,
+                entry_type: EntryType::Regular,
+                link_name: "",
+                size: 0,
+            }
+        }
+
+        fn link(path: &'a str, entry_type: EntryType, link_name: &'a str) -> Self {
+            Self {
+                path,
+                entry_type,
+                link_name,
+                size: 0,
+            }
+        }
+    }
+
+    /// Builds a tarball writing the names directly into the headers, bypassing the
+    /// validation `tar::Builder` does.
+    fn tarball(entries: &[RawEntry]) -> Vec<u8> {
+        let mut bytes = vec![];
+        for entry in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..entry.path.len()].copy_from_slice(entry.path.as_bytes());
+            header.as_old_mut().linkname[..entry.link_name.len()]
+                .copy_from_slice(entry.link_name.as_bytes());
+            header.set_entry_type(entry.entry_type);
+            header.set_mode(0o644);
+            header.set_size(entry.size);
+            header.set_cksum();
+            bytes.extend_from_slice(header.as_bytes());
+        }
+        bytes.extend_from_slice(&[0; 1024]);
+        bytes
+    }
+
+    fn unpack(entries: &[RawEntry]) -> (tempfile::TempDir, Result<(), Error>) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dst = temp_dir.path().join("dst");
+        std::fs::create_dir(&dst).unwrap();
+        let result = unpack_tar(Archive::new(Cursor::new(tarball(entries))), &dst);
+        (temp_dir, result)
+    }
+
+    #[test]
+    fn test_unpack_regular_entries() {
+        let (temp_dir, result) = unpack(&[
+            RawEntry::file("./ruby-2.7.0/configure"),
+            RawEntry::link("ruby-2.7.0/lib/link", EntryType::Symlink, "../configure"),
+            RawEntry::link(
+                "ruby-2.7.0/hardlink",
+                EntryType::Link,
+                "ruby-2.7.0/configure",
+            ),
+        ]);
+        result.unwrap();
+        let dir = temp_dir.path().join("dst").join("ruby-2.7.0");
+        assert!(dir.join("configure").exists());
+        assert!(dir.join("lib").join("link").exists());
+        assert!(dir.join("hardlink").exists());
+    }
+
+    #[test]
+    fn test_reject_absolute_path() {
+        let (_temp_dir, result) = unpack(&[RawEntry::file("/tmp/frum-evil")]);
+        assert!(matches!(result, Err(Error::AbsolutePath { .. })));
+    }
+
+    #[test]
+    fn test_reject_path_traversal() {
+        let (temp_dir, result) = unpack(&[RawEntry::file("ruby-2.7.0/../../evil")]);
+        assert!(matches!(result, Err(Error::PathTraversal { .. })));
+        assert!(!temp_dir.path().join("evil").exists());
+    }
+
+    #[test]
+    fn test_reject_escaping_symlink() {
+        for target in &["../../..", "/etc", "a/../../.."] {
+            let (_temp_dir, result) = unpack(&[RawEntry::link(
+                "ruby-2.7.0/link",
+                EntryType::Symlink,
+                target,
+            )]);
+            assert!(matches!(result, Err(Error::LinkOutsideTarget { .. })));
+        }
+    }
+
+    #[test]
+    fn test_reject_escaping_hardlink() {
+        let (_temp_dir, result) = unpack(&[RawEntry::link(
+            "ruby-2.7.0/link",
+            EntryType::Link,
+            "/etc/passwd",
+        )]);
+        assert!(matches!(result, Err(Error::LinkOutsideTarget { .. })));
+    }
+
+    #[test]
+    fn test_reject_write_through_symlink() {
+        let (temp_dir, result) = unpack(&[
+            RawEntry::link("dir", EntryType::Symlink, "."),
+            RawEntry::link("dir/up", EntryType::Symlink, ".."),
+            RawEntry::file("dir/up/evil"),
+        ]);
+        assert!(matches!(result, Err(Error::LinkOutsideTarget { .. })));
+        assert!(!temp_dir.path().join("dst").join("up").exists());
+        assert!(!temp_dir.path().join("evil").exists());
+    }
+
+    #[test]
+    fn test_reject_special_files() {
+        for entry_type in &[EntryType::Char, EntryType::Block, EntryType::Fifo] {
+            let (_temp_dir, result) = unpack(&[RawEntry::link("ruby-2.7.0/dev", *entry_type, "")]);
+            assert!(matches!(result, Err(Error::UnsupportedEntry { .. })));
+        }
+    }
+
+    #[test]
+    fn test_reject_large_entry() {
+        let (_temp_dir, result) = unpack(&[RawEntry {
+            size: MAX_ENTRY_SIZE + 1,
+            ..RawEntry::file("ruby-2.7.0/huge")
+        }]);
+        assert!(matches!(result, Err(Error::EntryTooLarge { .. })));
+    }
+}
@@ -1,30 +1,31 @@
 use crate::archive::extract::{Error, Extract};
+use crate::archive::unpack::MAX_ENTRY_SIZE;
 use log::debug;
-use reqwest::blocking::Response;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use tempfile::tempfile;
 use zip::read::ZipArchive;
 
-pub struct Zip {
-    response: Response,
+pub struct Zip<R: Read> {
+    response: R,
 }
 
-impl Zip {
+impl<R: Read> Zip<R> {
     #[allow(dead_code)]
-    pub fn new(response: Response) -> Self {
+    pub fn new(response: R) -> Self {
         Self { response }
     }
 }
 
-impl Extract for Zip {
+impl<R: Read> Extract for Zip<R> {
     fn extract_into<P: AsRef<Path>>(mut self, path: P) -> Result<(), Error> {
         let path = path.as_ref();
         let mut tmp_zip_file = tempfile().expect("Can't get a temporary file");
 
         debug!("Created a temporary zip file");
-        self.response.copy_to(&mut tmp_zip_file)?;
+        io::copy(&mut self.response, &mut tmp_zip_file)?;
         debug!(
             "Wrote zipfile successfully. Now extracting into {}.",
             path.display()
@@ -34,7 +35,20 @@ impl Extract for Zip {
 
         for i in 0..archive.len() {
             let mut file = archive.by_index(i)?;
-            let outpath = path.join(file.mangled_name());
+            let outpath = match file.enclosed_name() {
+                Some(name) => path.join(name),
+                None => {
+                    return Err(Error::PathTraversal {
+                        path: PathBuf::from(file.name()),
+                    })
+                }
+            };
+            if file.size() > MAX_ENTRY_SIZE {
+                return Err(Error::EntryTooLarge {
+                    path: PathBuf::from(file.name()),
+                    size: file.size(),
+                });
+            }
 
             {
                 let comment = file.comment();
@@ -97,4 +111,22 @@ mod tests {
         let node_file = temp_dir.as_ref().join("ruby-1.8.7").join("configure");
         assert!(node_file.exists());
     }
+
+    #[test]
+    fn test_reject_path_traversal() {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+        writer
+            .start_file("../evil", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(b"evil").unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dst = temp_dir.path().join("dst");
+        let result = Zip::new(&archive[..]).extract_into(&dst);
+        assert!(matches!(result, Err(Error::PathTraversal { .. })));
+        assert!(!temp_dir.path().join("evil").exists());
+    }
 }
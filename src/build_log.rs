@@ -0,0 +1,235 @@
+use crate::config::FrumConfig;
+use log::debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S%3f";
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A log file at `<logs_dir>/<version>-<timestamp>.log` collecting the output
+/// of every phase of a build.
+pub struct BuildLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl BuildLog {
+    /// Never overwrites an existing log: a log created at the same time gets a suffix.
+    pub fn create(config: &FrumConfig, name: &str) -> std::io::Result<Self> {
+        let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+        let mut suffix = String::new();
+        for attempt in 1.. {
+            let path = config
+                .logs_dir()
+                .join(format!("{}-{}{}.log", name, timestamp, suffix));
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => {
+                    debug!("build log: {}", path.display());
+                    return Ok(Self { path, file });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    suffix = format!("_{}", attempt);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        unreachable!()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs `command` with both its stdout and stderr appended to the log,
+    /// reporting every line of output to `observer` as it arrives.
+    pub fn run<O: BuildObserver>(
+        &mut self,
+        phase: &str,
+        command: &mut Command,
+        observer: &mut O,
+    ) -> std::io::Result<ExitStatus> {
+        writeln!(
+            self.file,
+            "==> {} ({})\n$ {:?}",
+            phase,
+            chrono::Local::now().to_rfc3339(),
+            command
+        )?;
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let readers = vec![
+            forward_lines(stdout, Stream::Stdout, sender.clone()),
+            forward_lines(stderr, Stream::Stderr, sender),
+        ];
+        loop {
+            match receiver.recv_timeout(TICK_INTERVAL) {
+                Ok((stream, line)) => {
+                    writeln!(self.file, "{}", line)?;
+                    observer.line(stream, &line);
+                }
+                Err(RecvTimeoutError::Timeout) => observer.tick(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        for reader in readers {
+            reader.join().expect("Can't read the output of the build");
+        }
+
+        let status = child.wait()?;
+        writeln!(self.file, "==> {} finished with {}\n", phase, status)?;
+        Ok(status)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives the output of a running build phase.
+pub trait BuildObserver {
+    fn line(&mut self, stream: Stream, line: &str);
+
+    /// Called periodically while the phase is running without printing anything.
+    fn tick(&mut self) {}
+}
+
+impl<F: FnMut(Stream, &str)> BuildObserver for F {
+    fn line(&mut self, stream: Stream, line: &str) {
+        self(stream, line)
+    }
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    stream: Stream,
+    sender: mpsc::Sender<(Stream, String)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buffer = vec![];
+        while let Ok(len) = reader.read_until(b'\n', &mut buffer) {
+            if len == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buffer)
+                .trim_end_matches(&['\r', '\n'][..])
+                .to_string();
+            if sender.send((stream, line)).is_err() {
+                break;
+            }
+            buffer.clear();
+        }
+    })
+}
+
+/// Returns the most recent log whose version name is accepted by `matches`.
+pub fn latest<F>(config: &FrumConfig, matches: F) -> std::io::Result<Option<PathBuf>>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut logs = vec![];
+    for entry in config.logs_dir().read_dir()? {
+        let path = entry?.path();
+        let (name, timestamp) = match path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .and_then(|x| x.strip_suffix(".log"))
+            .and_then(|x| x.rsplit_once('-'))
+        {
+            Some(parts) => parts,
+            None => continue,
+        };
+        if matches(name) {
+            logs.push((timestamp.to_string(), path.clone()));
+        }
+    }
+    Ok(logs.into_iter().max().map(|(_, path)| path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_run_writes_stdout_and_stderr() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let mut log = BuildLog::create(&config, "2.7.0").unwrap();
+        let mut lines = vec![];
+        let status = log
+            .run(
+                "configure",
+                Command::new("sh")
+                    .arg("-c")
+                    .arg("echo out; sleep 0.1; echo err >&2"),
+                &mut |stream: Stream, line: &str| lines.push((stream, line.to_string())),
+            )
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(
+            lines,
+            vec![
+                (Stream::Stdout, "out".to_string()),
+                (Stream::Stderr, "err".to_string())
+            ]
+        );
+
+        let contents = std::fs::read_to_string(log.path()).unwrap();
+        assert!(contents.contains("==> configure"));
+        assert!(contents.contains("out\nerr\n"));
+    }
+
+    #[test]
+    fn test_create_keeps_existing_logs() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let paths = (0..3)
+            .map(|_| BuildLog::create(&config, "2.7.0").unwrap().path)
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(paths.len(), 3);
+    }
+
+    #[test]
+    fn test_latest() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        for name in &[
+            "2.7.0-20210101000000.log",
+            "2.7.0-20210301000000.log",
+            "2.6.4-20210401000000.log",
+        ] {
+            File::create(config.logs_dir().join(name)).unwrap();
+        }
+
+        assert_eq!(
+            latest(&config, |name| name == "2.7.0").unwrap(),
+            Some(config.logs_dir().join("2.7.0-20210301000000.log"))
+        );
+        assert_eq!(latest(&config, |name| name == "3.0.0").unwrap(), None);
+    }
+}
@@ -0,0 +1,69 @@
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+/// A reader that computes the SHA-256 digest of everything read through it.
+pub struct Sha256Reader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Sha256Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Consumes the rest of the stream and returns the hex encoded digest.
+    pub fn finish(mut self) -> std::io::Result<String> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<R: Read> Read for Sha256Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Whether `digest` is a hex encoded SHA-256 digest as written by `Sha256Reader`.
+pub fn is_sha256(digest: &str) -> bool {
+    digest.len() == 64
+        && digest
+            .bytes()
+            .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_sha256, Sha256Reader};
+    use std::io::Read;
+
+    #[test]
+    fn test_sha256_of_partially_read_stream() {
+        let mut reader = Sha256Reader::new(&b"abc"[..]);
+        let mut first = [0; 1];
+        reader.read_exact(&mut first).unwrap();
+        assert_eq!(
+            reader.finish().unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_is_sha256() {
+        assert!(is_sha256(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        ));
+        assert!(!is_sha256(
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+        ));
+        assert!(!is_sha256("ba7816bf"));
+        assert!(!is_sha256(".."));
+        assert!(!is_sha256("/home/u/Downloads"));
+    }
+}
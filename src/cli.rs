@@ -1,4 +1,4 @@
-use clap::{App, AppSettings, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
 
 pub fn build_cli() -> App<'static, 'static> {
     App::new("frum")
@@ -23,8 +23,51 @@ pub fn build_cli() -> App<'static, 'static> {
                 .help("The root directory of frum installations [default: $HOME/.frum]")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .help("Uses only the cached index and downloads instead of the network"),
+        )
+        .arg(
+            Arg::with_name("index-ttl")
+                .long("index-ttl")
+                .help("Seconds to use the cached index before revalidating it [default: 3600]. You can also set $FRUM_INDEX_TTL")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("version-files")
+                .long("version-files")
+                .help("Comma separated version files to look for, in order of priority [default: ruby-version,tool-versions,mise,gemfile-lock,gemfile]. You can also set $FRUM_VERSION_FILES")
+                .takes_value(true)
+                .validator(|names| crate::version_file::parse_version_files(&names).map(|_| ())),
+        )
         .subcommand(
-            SubCommand::with_name("init").about("Sets environment variables for initializing frum"),
+            SubCommand::with_name("init")
+                .about("Sets environment variables for initializing frum")
+                .arg(
+                    Arg::with_name("shell")
+                        .long("shell")
+                        .takes_value(true)
+                        .possible_values(crate::shell::AVAILABLE_SHELLS)
+                        .help("The shell to set up instead of inferring it from the parent processes"),
+                )
+                .arg(
+                    Arg::with_name("shims")
+                        .long("shims")
+                        .help("Puts the shims directory on PATH instead of switching versions on cd"),
+                )
+                .arg(
+                    Arg::with_name("hook")
+                        .long("hook")
+                        .takes_value(true)
+                        .possible_values(&["prompt", "cd"])
+                        .conflicts_with("shims")
+                        .help("Switches versions before every prompt or only when running cd [default: prompt]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rehash")
+                .about("Regenerates the shims for the executables of the installed versions"),
         )
         .subcommand(
             SubCommand::with_name("install")
@@ -37,6 +80,51 @@ pub fn build_cli() -> App<'static, 'static> {
                         .long("list")
                         .help("Lists Ruby versions available to install"),
                 )
+                .arg(
+                    Arg::with_name("include-prerelease")
+                        .long("include-prerelease")
+                        .help("Includes previews and release candidates when listing versions or resolving a partial version"),
+                )
+                .arg(
+                    Arg::with_name("skip-checksum")
+                        .long("skip-checksum")
+                        .help("Skips verifying the downloaded archive against the checksum in the index"),
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .long("verbose")
+                        .help("Streams the output of configure and make to the terminal"),
+                )
+                .arg(
+                    Arg::with_name("from-file")
+                        .long("from-file")
+                        .takes_value(true)
+                        .value_name("archive")
+                        .help("Builds Ruby from a local source archive instead of downloading it"),
+                )
+                .arg(
+                    Arg::with_name("from-dir")
+                        .long("from-dir")
+                        .takes_value(true)
+                        .value_name("dir")
+                        .help("Builds Ruby from a local source directory instead of downloading it"),
+                )
+                .arg(
+                    Arg::with_name("as")
+                        .long("as")
+                        .takes_value(true)
+                        .value_name("version")
+                        .requires("local-source")
+                        .validator(|name| match crate::version::Version::parse(&name) {
+                            Ok(crate::version::Version::System) | Err(_) => Err(
+                                "The name must be a full version like 3.3.0 or jruby-9.4.5.0"
+                                    .to_string(),
+                            ),
+                            Ok(_) => Ok(()),
+                        })
+                        .help("Installs a local source as this version instead of detecting it"),
+                )
+                .group(ArgGroup::with_name("local-source").args(&["from-file", "from-dir"]))
                 .arg(Arg::with_name("version").index(1))
                 .arg(
                     Arg::with_name("configure_opts")
@@ -50,9 +138,64 @@ pub fn build_cli() -> App<'static, 'static> {
                 .arg(Arg::with_name("version").index(1).required(true)),
         )
         .subcommand(SubCommand::with_name("versions").about("Lists installed Ruby versions"))
+        .subcommand(
+            SubCommand::with_name("exec")
+                .setting(AppSettings::TrailingVarArg)
+                .about("Runs a command with a specific Ruby version")
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .help("The Ruby version to use instead of the version files"),
+                )
+                .arg(
+                    Arg::with_name("command")
+                        .multiple(true)
+                        .required(true)
+                        .help("The command to run, e.g. bundle exec rspec"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("which")
+                .about("Prints the path of an executable of the current Ruby version")
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .help("The Ruby version to look in instead of the version files"),
+                )
+                .arg(Arg::with_name("command").index(1).required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("current")
+                .about("Prints the current Ruby version and where it is set")
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints the version and its source as JSON"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("local")
-                .about("Sets the current Ruby version")
+                .about("Sets the current Ruby version and writes it to .ruby-version")
+                .arg(
+                    Arg::with_name("unset")
+                        .long("unset")
+                        .conflicts_with_all(&["version", "no-write"])
+                        .help("Removes .ruby-version from the current directory"),
+                )
+                .arg(
+                    Arg::with_name("no-write")
+                        .long("no-write")
+                        .requires("version")
+                        .help("Switches the version of the current shell without writing .ruby-version"),
+                )
+                .arg(
+                    Arg::with_name("if-changed")
+                        .long("if-changed")
+                        .conflicts_with_all(&["version", "unset"])
+                        .help("Skips switching when the version files haven't changed since the last switch"),
+                )
                 .arg(Arg::with_name("version").index(1)),
         )
         .subcommand(
@@ -60,6 +203,22 @@ pub fn build_cli() -> App<'static, 'static> {
                 .about("Sets the global Ruby version")
                 .arg(Arg::with_name("version").index(1).required(true)),
         )
+        .subcommand(
+            SubCommand::with_name("logs")
+                .about("Prints the most recent build log of a specific Ruby version")
+                .arg(Arg::with_name("version").index(1).required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("cache")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .about("Manages the cache of downloaded Ruby archives")
+                .subcommand(SubCommand::with_name("list").about("Lists cached archives"))
+                .subcommand(SubCommand::with_name("clean").about("Removes all cached archives"))
+                .subcommand(
+                    SubCommand::with_name("prune")
+                        .about("Removes corrupted archives and interrupted downloads"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("completions")
                 .about("Print shell completions to stdout")
@@ -68,6 +227,7 @@ pub fn build_cli() -> App<'static, 'static> {
                         .short("s")
                         .long("shell")
                         .help("The shell syntax to use")
+                        .possible_values(&clap::Shell::variants())
                         .takes_value(true),
                 )
                 .arg(
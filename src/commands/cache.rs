@@ -0,0 +1,116 @@
+use crate::config::FrumConfig;
+use crate::download_cache;
+use crate::outln;
+use colored::Colorize;
+use log::debug;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(Debug)]
+pub enum CacheCommand {
+    List,
+    Clean,
+    Prune,
+}
+
+pub struct Cache {
+    pub command: CacheCommand,
+}
+
+impl crate::command::Command for Cache {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        match self.command {
+            CacheCommand::List => {
+                for archive in download_cache::list(config)? {
+                    outln!(
+                        config#Info,
+                        "{} {} sha256:{}",
+                        archive.file_name(),
+                        format!("({})", human_readable_size(archive.size())).dimmed(),
+                        archive.sha256
+                    );
+                }
+            }
+            CacheCommand::Clean => {
+                download_cache::clean(config)?;
+                outln!(config#Info, "{} Removed all cached archives", "==>".green());
+            }
+            CacheCommand::Prune => {
+                let mut removed = 0;
+                for path in download_cache::partial_downloads(config)? {
+                    debug!("remove {}", path.display());
+                    if path.is_dir() {
+                        std::fs::remove_dir_all(&path)?;
+                    } else {
+                        std::fs::remove_file(&path)?;
+                    }
+                    removed += 1;
+                }
+                for archive in download_cache::list(config)? {
+                    if !archive.is_valid() {
+                        debug!("remove {}", archive.path.display());
+                        archive.remove(config)?;
+                        removed += 1;
+                    }
+                }
+                outln!(config#Info, "{} Removed {} broken cache entries", "==>".green(), removed);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn human_readable_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{human_readable_size, Cache, CacheCommand};
+    use crate::command::Command;
+    use crate::config::FrumConfig;
+    use crate::download_cache;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_human_readable_size() {
+        assert_eq!(human_readable_size(512), "512 B");
+        assert_eq!(human_readable_size(20_552_324), "19.6 MiB");
+    }
+
+    #[test]
+    fn test_prune_removes_partial_downloads() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let partial_download = download_cache::temp_file(&config).unwrap();
+        let (_, path) = partial_download.keep().unwrap();
+
+        Cache {
+            command: CacheCommand::Prune,
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(!path.exists());
+    }
+}
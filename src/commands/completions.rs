@@ -2,15 +2,16 @@ use crate::cli::build_cli;
 use crate::command::Command;
 use crate::config::FrumConfig;
 use crate::outln;
-use crate::shell::{infer_shell, AVAILABLE_SHELLS};
-use crate::version::{is_dotfile, Version};
+use crate::shell::infer_shell;
+use crate::version::installed_versions;
 use clap::Shell;
 use thiserror::Error;
 
 const USE_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
-const INSTALL_COMMAND_REGEX: &str = r#"opts=" -l -h -V  --list --help --version  "#;
+const INSTALL_COMMAND_REGEX: &str = r#"opts=" -l -h -V  --list --include-prerelease --skip-checksum --verbose --help --version --from-file --from-dir --as  "#;
 const UNINSTALL_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
-const LOCAL_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
+const LOCAL_COMMAND_REGEX: &str =
+    r#"opts=" -h -V  --unset --no-write --if-changed --help --version  "#;
 
 #[derive(Debug)]
 enum FrumCommand {
@@ -31,10 +32,15 @@ pub enum FrumError {
         shells_as_string()
     )]
     CantInferShell,
+    #[error(
+        "Shell completions are not supported for {shell}. Use --shell to choose one of:\n{}",
+        shells_as_string()
+    )]
+    UnsupportedShell { shell: String },
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
-    SemverError(#[from] semver::SemVerError),
+    VersionParseError(#[from] crate::version::ParseError),
 }
 
 pub struct Completions {
@@ -47,34 +53,23 @@ impl Command for Completions {
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
         if self.list {
-            for entry in config
-                .versions_dir()
-                .read_dir()
-                .map_err(FrumError::IoError)?
-            {
-                let entry = entry.map_err(FrumError::IoError)?;
-                if is_dotfile(&entry) {
-                    continue;
-                }
-
-                let path = entry.path();
-                let filename = path
-                    .file_name()
-                    .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                    .map_err(FrumError::IoError)?
-                    .to_str()
-                    .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                    .map_err(FrumError::IoError)?;
-                let version = Version::parse(filename).map_err(FrumError::SemverError)?;
+            for version in installed_versions(config).map_err(FrumError::IoError)? {
                 outln!(config#Info, "{} {}", " ", version);
             }
             return Ok(());
         }
 
-        let shell = self
-            .shell
-            .or_else(|| infer_shell().map(Into::into))
-            .ok_or(FrumError::CantInferShell)?;
+        let shell = match self.shell {
+            Some(shell) => shell,
+            None => {
+                let shell = infer_shell().ok_or(FrumError::CantInferShell)?;
+                shell
+                    .as_clap_shell()
+                    .ok_or_else(|| FrumError::UnsupportedShell {
+                        shell: format!("{:?}", shell),
+                    })?
+            }
+        };
 
         print!("{}", customize_completions(shell));
         Ok(())
@@ -248,8 +243,9 @@ fi"#
     }
 }
 
+/// The shells clap can generate completions for.
 fn shells_as_string() -> String {
-    AVAILABLE_SHELLS
+    Shell::variants()
         .iter()
         .map(|x| format!("* {}", x))
         .collect::<Vec<_>>()
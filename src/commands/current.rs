@@ -0,0 +1,227 @@
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{current_version, default_version, Version};
+use crate::version_file::{find_version_file, VersionFile};
+use colored::Colorize;
+use log::debug;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+}
+
+pub struct Current {
+    pub json: bool,
+}
+
+/// Where the active version comes from.
+#[derive(Debug, PartialEq, Eq)]
+enum Source {
+    Env,
+    File {
+        kind: VersionFile,
+        path: PathBuf,
+    },
+    /// Switched with `frum local --no-write`, so only the shell knows about it.
+    Shell,
+    Default,
+    System,
+}
+
+#[derive(Debug)]
+struct ActiveVersion {
+    /// The requested version, which is only a pattern when it isn't installed.
+    version: String,
+    installed: bool,
+    source: Source,
+}
+
+impl crate::command::Command for Current {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let active = active_version(config, &std::env::current_dir()?)?;
+        debug!("active version: {:?}", active);
+        if self.json {
+            let path = match &active.source {
+                Source::File { path, .. } => Some(path),
+                _ => None,
+            };
+            let json = serde_json::json!({
+                "version": active.version,
+                "installed": active.installed,
+                "source": active.source.name(),
+                "path": path,
+            });
+            outln!(config#Info, "{}", serde_json::to_string(&json)?);
+            return Ok(());
+        }
+        let version = if active.installed {
+            active.version.green()
+        } else {
+            active.version.red()
+        };
+        match (&active.source, active.installed) {
+            (Source::System, _) => outln!(config#Info, "{}", version),
+            (source, true) => outln!(config#Info, "{} (set by {})", version, source),
+            (source, false) => {
+                outln!(config#Info, "{} (set by {}, not installed)", version, source)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Source {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Env => "env",
+            Self::File { kind, .. } => kind.name(),
+            Self::Shell => "shell",
+            Self::Default => "default",
+            Self::System => "system",
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Env => f.write_str("the FRUM_VERSION environment variable"),
+            Self::File { path, .. } => write!(f, "{}", path.display()),
+            Self::Shell => f.write_str("frum local --no-write"),
+            Self::Default => f.write_str("the global default"),
+            Self::System => f.write_str("the system Ruby"),
+        }
+    }
+}
+
+/// Resolves the version requested for `dir` the same way `frum local` does. When the
+/// shell already switched to a different version, that version wins.
+fn active_version(config: &FrumConfig, dir: &Path) -> Result<ActiveVersion, FrumError> {
+    let (requested, source) = if let Some(version) = &config.env_version {
+        (Some(version.clone()), Source::Env)
+    } else if let Some(found) = find_version_file(config, dir.to_path_buf()) {
+        let source = Source::File {
+            kind: found.kind,
+            path: found.path,
+        };
+        (Some(found.version), source)
+    } else if let Some(version) = default_version(config) {
+        (Some(InputVersion::Full(version)), Source::Default)
+    } else {
+        (None, Source::System)
+    };
+    let resolved = match &requested {
+        Some(version) => version.to_installed_version(config)?,
+        None => Some(Version::System),
+    }
+    .filter(|version| {
+        *version == Version::System || config.versions_dir().join(version.to_string()).exists()
+    });
+    // Without a multishell path (e.g. in an editor) there is no shell state to check.
+    let shell_version = match config.frum_path {
+        Some(_) => current_version(config).ok().flatten(),
+        None => None,
+    };
+    if let (Some(resolved), Some(shell_version)) = (&resolved, shell_version) {
+        if source != Source::Env && *resolved != shell_version {
+            return Ok(ActiveVersion {
+                version: shell_version.to_string(),
+                installed: true,
+                source: Source::Shell,
+            });
+        }
+    }
+    Ok(match resolved {
+        Some(version) => ActiveVersion {
+            version: version.to_string(),
+            installed: true,
+            source,
+        },
+        None => ActiveVersion {
+            version: requested.map(|v| v.to_string()).unwrap_or_default(),
+            installed: false,
+            source,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::config_with_versions;
+    use crate::symlink::create_symlink_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_current_sources() {
+        let config = config_with_versions(&["2.7.0", "3.2.2"]);
+        let project = tempdir().unwrap();
+
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "system");
+        assert_eq!(active.source, Source::System);
+
+        create_symlink_dir(
+            config.versions_dir().join("2.7.0"),
+            config.default_version_dir(),
+        )
+        .unwrap();
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "2.7.0");
+        assert_eq!(active.source, Source::Default);
+
+        let path = project.path().join(".tool-versions");
+        std::fs::write(&path, "ruby 3.2\n").unwrap();
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "3.2.2");
+        assert!(active.installed);
+        assert_eq!(
+            active.source,
+            Source::File {
+                kind: VersionFile::ToolVersions,
+                path
+            }
+        );
+
+        let config = FrumConfig {
+            env_version: Some(InputVersion::Full(Version::parse("2.7.0").unwrap())),
+            ..config
+        };
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "2.7.0");
+        assert_eq!(active.source, Source::Env);
+    }
+
+    #[test]
+    fn test_current_shell_and_missing_versions() {
+        let config = config_with_versions(&["2.7.0", "3.2.2"]);
+        let project = tempdir().unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "3.3.0\n").unwrap();
+
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "3.3.0");
+        assert!(!active.installed);
+
+        create_symlink_dir(
+            config.versions_dir().join("2.7.0"),
+            config.frum_path.as_ref().unwrap(),
+        )
+        .unwrap();
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "3.3.0");
+        assert!(!active.installed);
+
+        std::fs::write(project.path().join(".ruby-version"), "3.2.2\n").unwrap();
+        let active = active_version(&config, project.path()).unwrap();
+        assert_eq!(active.version, "2.7.0");
+        assert_eq!(active.source, Source::Shell);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::commands::which::{bin_dirs, system_path};
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::version::{default_version, Version};
+use crate::version_file::get_user_version_for_directory;
+use log::debug;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    JoinPathsError(#[from] std::env::JoinPathsError),
+    #[error("Requested version {version} is not currently installed")]
+    VersionNotFound { version: InputVersion },
+    #[error("Can't find version in dotfiles. Please provide a version with --version.")]
+    CantInferVersion,
+    #[error("Can't find command: {command}")]
+    CommandNotFound { command: String },
+}
+
+pub struct Exec {
+    pub version: Option<InputVersion>,
+    pub command: Vec<String>,
+}
+
+impl crate::command::Command for Exec {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let version = resolve_version(config, self.version.clone())?;
+        debug!("exec {:?} with Ruby {}", self.command, version);
+        let mut command = build_command(config, &version, &self.command[0], &self.command[1..])?;
+        run(&mut command).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => FrumError::CommandNotFound {
+                command: self.command[0].clone(),
+            },
+            _ => FrumError::IoError(err),
+        })
+    }
+}
+
+/// Resolves `requested`, then the version files of the current directory, then the
+/// default alias to an installed version.
+pub(crate) fn resolve_version(
+    config: &FrumConfig,
+    requested: Option<InputVersion>,
+) -> Result<Version, FrumError> {
+    let current_dir = std::env::current_dir()?;
+    let requested = requested
+        .or_else(|| get_user_version_for_directory(config, current_dir))
+        .or_else(|| default_version(config).map(InputVersion::Full))
+        .ok_or(FrumError::CantInferVersion)?;
+    match requested.to_installed_version(config)? {
+        Some(version)
+            if version == Version::System
+                || config.versions_dir().join(version.to_string()).exists() =>
+        {
+            Ok(version)
+        }
+        _ => Err(FrumError::VersionNotFound { version: requested }),
+    }
+}
+
+/// Builds a command with the executables of `version` at the front of `PATH` and the
+/// shims removed from it. Nested frum calls see the version through `$FRUM_VERSION`.
+pub(crate) fn build_command<P: AsRef<OsStr>, A: AsRef<OsStr>>(
+    config: &FrumConfig,
+    version: &Version,
+    program: P,
+    args: &[A],
+) -> Result<std::process::Command, FrumError> {
+    let mut process = std::process::Command::new(program);
+    process.args(args);
+    process.env("FRUM_VERSION", version.to_string());
+    let paths = system_path(config);
+    if *version == Version::System {
+        process.env("PATH", std::env::join_paths(paths)?);
+        return Ok(process);
+    }
+    let paths = bin_dirs(config, version).into_iter().chain(paths);
+    process.env("PATH", std::env::join_paths(paths)?);
+    // Gems installed for another frum Ruby are built against a different ABI.
+    let version_dir = config.versions_dir().join(version.to_string());
+    for name in &["GEM_HOME", "GEM_PATH"] {
+        if let Some(value) = std::env::var_os(name) {
+            if points_to_other_version(config, &version_dir, &value) {
+                debug!("unset {}", name);
+                process.env_remove(name);
+            }
+        }
+    }
+    Ok(process)
+}
+
+fn points_to_other_version(config: &FrumConfig, version_dir: &Path, value: &OsString) -> bool {
+    std::env::split_paths(value)
+        .any(|path| path.starts_with(config.versions_dir()) && !path.starts_with(version_dir))
+}
+
+/// Replaces the current process with `command`, so it only returns on failure.
+#[cfg(unix)]
+pub(crate) fn run(command: &mut std::process::Command) -> std::io::Result<()> {
+    use std::os::unix::process::CommandExt;
+    Err(command.exec())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn run(command: &mut std::process::Command) -> std::io::Result<()> {
+    let status = command.status()?;
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_exec_environment() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        let version_dir = config.versions_dir().join("3.2.2");
+        std::fs::create_dir_all(version_dir.join("bin")).unwrap();
+
+        let output = build_command(
+            &config,
+            &Version::parse("3.2.2").unwrap(),
+            "sh",
+            &["-c", "echo \"$FRUM_VERSION $PATH\"; exit 3"],
+        )
+        .unwrap()
+        .output()
+        .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert!(stdout.starts_with(&format!("3.2.2 {}:", version_dir.join("bin").display())));
+    }
+
+    #[test]
+    fn test_exec_gem_paths() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        let version_dir = config.versions_dir().join("3.2.2");
+        let other_gems = config
+            .versions_dir()
+            .join("2.7.0")
+            .join("lib/ruby/gems/2.7.0");
+        let own_gems = version_dir.join("lib/ruby/gems/3.2.0");
+        assert!(points_to_other_version(
+            &config,
+            &version_dir,
+            &other_gems.into()
+        ));
+        assert!(!points_to_other_version(
+            &config,
+            &version_dir,
+            &own_gems.into()
+        ));
+        assert!(!points_to_other_version(
+            &config,
+            &version_dir,
+            &OsString::from("/home/user/.gem")
+        ));
+    }
+}
@@ -23,15 +23,19 @@ impl crate::command::Command for Global {
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
         debug!("Use {} as the default version", &self.version);
-        let version = match self.version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
-            version => return Err(FrumError::VersionNotFound { version }),
-        };
-        if !&config
-            .versions_dir()
-            .join(self.version.to_string())
-            .exists()
+        let version = match self
+            .version
+            .to_installed_version(config)
+            .map_err(FrumError::IoError)?
         {
+            Some(version @ Version::Ruby(_)) | Some(version @ Version::Engine(_)) => version,
+            _ => {
+                return Err(FrumError::VersionNotFound {
+                    version: self.version.clone(),
+                })
+            }
+        };
+        if !&config.versions_dir().join(version.to_string()).exists() {
             return Err(FrumError::VersionNotFound {
                 version: self.version.clone(),
             });
@@ -67,7 +71,7 @@ mod tests {
         File::create(dir_path.join("ruby")).unwrap();
 
         Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.6.4").unwrap())),
+            version: InputVersion::Full(Version::parse("2.6.4").unwrap()),
         }
         .apply(&config)
         .expect("failed to install");
@@ -95,7 +99,7 @@ mod tests {
         File::create(dir_path.join("ruby")).unwrap();
 
         let result = Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.7.0").unwrap())),
+            version: InputVersion::Full(Version::parse("2.7.0").unwrap()),
         }
         .apply(&config);
         match result {
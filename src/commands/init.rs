@@ -1,5 +1,6 @@
-use crate::shell::infer_shell;
+use crate::commands::rehash::rehash;
 use crate::shell::Shell;
+use crate::shell::{infer_shell, AVAILABLE_SHELLS};
 use crate::symlink::create_symlink_dir;
 use thiserror::Error;
 
@@ -9,28 +10,54 @@ pub enum FrumError {
     HttpError(#[from] reqwest::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
-    #[error("Can't infer shell!")]
+    #[error("Can't infer shell! Use --shell to choose one of: {}", AVAILABLE_SHELLS.join(", "))]
     CantInferShell,
+    #[error(transparent)]
+    RehashError(#[from] crate::commands::rehash::FrumError),
+}
+
+pub struct Init {
+    /// The shell to print the setup for instead of inferring it.
+    pub shell: Option<Box<dyn Shell>>,
+    /// Puts the shims directory on `PATH` instead of switching versions on `cd`.
+    pub shims: bool,
+    pub hook: Hook,
 }
 
-pub struct Init {}
+/// When the shell switches to the version of the current directory.
+pub enum Hook {
+    Prompt,
+    Cd,
+}
 
 impl crate::command::Command for Init {
     type Error = FrumError;
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
-        let shell: Box<dyn Shell> = infer_shell().ok_or(FrumError::CantInferShell)?;
-        let frum_path = create_symlink(&config);
-        let binary_path = if cfg!(windows) {
-            frum_path.clone()
-        } else {
-            frum_path.join("bin")
+        let inferred_shell;
+        let shell: &dyn Shell = match &self.shell {
+            Some(shell) => shell.as_ref(),
+            None => {
+                inferred_shell = infer_shell().ok_or(FrumError::CantInferShell)?;
+                inferred_shell.as_ref()
+            }
         };
-        println!("{}", shell.path(&binary_path));
-        println!(
-            "{}",
-            shell.set_env_var("FRUM_MULTISHELL_PATH", frum_path.to_str().unwrap())
-        );
+        if self.shims {
+            rehash(config)?;
+            println!("{}", shell.path(&config.shims_dir()));
+        } else {
+            let frum_path = create_symlink(&config);
+            let binary_path = if cfg!(windows) {
+                frum_path.clone()
+            } else {
+                frum_path.join("bin")
+            };
+            println!("{}", shell.path(&binary_path));
+            println!(
+                "{}",
+                shell.set_env_var("FRUM_MULTISHELL_PATH", frum_path.to_str().unwrap())
+            );
+        }
         println!(
             "{}",
             shell.set_env_var("FRUM_DIR", config.base_dir().to_str().unwrap())
@@ -43,7 +70,24 @@ impl crate::command::Command for Init {
             "{}",
             shell.set_env_var("FRUM_RUBY_BUILD_MIRROR", config.ruby_build_mirror.as_str())
         );
-        println!("{}", shell.use_on_cd(&config));
+        println!(
+            "{}",
+            shell.set_env_var(
+                "FRUM_VERSION_FILES",
+                &config
+                    .version_files
+                    .iter()
+                    .map(|version_file| version_file.name())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        );
+        if !self.shims {
+            match self.hook {
+                Hook::Prompt => println!("{}", shell.use_on_prompt(config)),
+                Hook::Cd => println!("{}", shell.use_on_cd(&config)),
+            }
+        }
         Ok(())
     }
 }
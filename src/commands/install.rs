@@ -1,15 +1,24 @@
-use crate::alias::create_alias;
-use crate::archive::{self, extract::Error as ExtractError, extract::Extract};
+use crate::alias::create_alias_if_missing;
+use crate::archive::{self, extract::Error as ExtractError, Format};
+use crate::build_log::BuildLog;
+use crate::checksum::Sha256Reader;
+use crate::commands::rehash::rehash;
 use crate::config::FrumConfig;
+use crate::download_cache::{self, CachedArchive};
+use crate::engine::Engine;
 use crate::input_version::InputVersion;
+use crate::lock::{self, FileLock};
 use crate::outln;
-use crate::version::Version;
+use crate::progress::Progress;
+use crate::remote_ruby_index::IndexedRubyVersion;
+use crate::version::{installed_versions, EngineVersion, Version};
 use crate::version_file::get_user_version_for_directory;
 use anyhow::Result;
 use colored::Colorize;
 use log::debug;
 use reqwest::Url;
 use std::io::prelude::*;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -29,136 +38,552 @@ pub enum FrumError {
     TarIsEmpty,
     #[error("Can't find version: {version}")]
     VersionNotFound { version: InputVersion },
-    #[error("Can't list the remote versions: {source:?}")]
-    CantListRemoteVersions { source: reqwest::Error },
+    #[error("Can't find a release of {version}. Use --include-prerelease to install a preview or release candidate.")]
+    OnlyPrereleaseFound { version: InputVersion },
+    #[error("Can't list the remote versions: {source}")]
+    CantListRemoteVersions {
+        source: crate::remote_ruby_index::Error,
+    },
+    #[error("Can't find an archive of Ruby {version} in the download cache. Run frum without --offline to download it.")]
+    ArchiveNotCached { version: Version },
     #[error("Version already installed at {path:?}")]
     VersionAlreadyInstalled { path: PathBuf },
     #[error("Can't find version in dotfiles. Please provide a version manually to the command.")]
     CantInferVersion,
     #[error("The requested version is not installable: {version}")]
     NotInstallableVersion { version: Version },
-    #[error("Can't build Ruby: {stderr}")]
-    CantBuildRuby { stderr: String },
+    #[error("Can't resolve {version} without a release index. Please provide a full version like jruby-9.4.5.0.")]
+    EngineVersionRequired { version: InputVersion },
+    #[error("{version} isn't available for this platform")]
+    UnsupportedPlatform { version: Version },
+    #[error(transparent)]
+    LockError(#[from] lock::Error),
+    #[error("Can't build Ruby: {phase} failed. See the build log at {log_path:?}")]
+    CantBuildRuby { phase: String, log_path: PathBuf },
+    #[error("Can't find the checksum of Ruby {version} in the index. Use --skip-checksum to install it anyway.")]
+    ChecksumNotFound { version: Version },
+    #[error(
+        "Can't find the checksum of {version} at {url}. Use --skip-checksum to install it anyway."
+    )]
+    EngineChecksumNotFound { version: Version, url: String },
+    #[error("Can't detect the Ruby version of {path:?}. Use --as to name the installation.")]
+    CantDetectVersion { path: PathBuf },
+    #[error(
+        "Can't install {version} from a local source. Use --as {version} to name the installation."
+    )]
+    VersionWithLocalSource { version: InputVersion },
+    #[error("Can't find the source directory {path:?}")]
+    SourceDirNotFound { path: PathBuf },
+    #[error(transparent)]
+    RehashError(#[from] crate::commands::rehash::FrumError),
+    #[error("Checksum mismatch for {archive}: expected {expected}, but got {actual}")]
+    ChecksumMismatch {
+        archive: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+pub enum LocalSource {
+    File(PathBuf),
+    Directory(PathBuf),
 }
 
 pub struct Install {
     pub version: Option<InputVersion>,
+    pub local_source: Option<LocalSource>,
+    pub name: Option<Version>,
     pub configure_opts: Vec<String>,
+    pub skip_checksum: bool,
+    pub verbose: bool,
+    pub include_prerelease: bool,
 }
 
 impl crate::command::Command for Install {
     type Error = FrumError;
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if let (Some(_), Some(version)) = (&self.local_source, &self.version) {
+            return Err(FrumError::VersionWithLocalSource {
+                version: version.clone(),
+            });
+        }
+        match &self.local_source {
+            Some(LocalSource::File(path)) => self.install_from_file(config, path),
+            Some(LocalSource::Directory(path)) => self.install_from_dir(config, path),
+            None => self.install_from_mirror(config),
+        }
+    }
+}
+
+impl Install {
+    fn install_from_mirror(&self, config: &FrumConfig) -> Result<(), FrumError> {
         let current_version = self
             .version
             .clone()
-            .or_else(|| get_user_version_for_directory(std::env::current_dir().unwrap()))
+            .or_else(|| get_user_version_for_directory(config, std::env::current_dir().unwrap()))
             .ok_or(FrumError::CantInferVersion)?;
+        let mut remote_versions = None;
         let version = match current_version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
+            InputVersion::Full(Version::Engine(version)) => {
+                return self.install_engine_from_mirror(config, version)
+            }
+            InputVersion::Full(version @ Version::Ruby(_)) => version,
             InputVersion::Full(Version::System) => {
                 return Err(FrumError::NotInstallableVersion {
                     version: Version::System,
                 })
             }
+            version @ InputVersion::Engine(..) => {
+                return Err(FrumError::EngineVersionRequired { version })
+            }
             current_version => {
-                let available_versions = crate::remote_ruby_index::list(&config.ruby_build_mirror)
-                    .map_err(|source| FrumError::CantListRemoteVersions { source })?
-                    .drain(..)
-                    .map(|x| x.version)
-                    .collect::<Vec<_>>();
-
-                current_version
-                    .to_version(&available_versions)
-                    .ok_or(FrumError::VersionNotFound {
-                        version: current_version,
-                    })?
-                    .clone()
+                // Offline, resolve against what we have: the cached index and installed versions.
+                let (versions, installed_versions) = if config.offline {
+                    (
+                        crate::remote_ruby_index::cached(config).unwrap_or_default(),
+                        installed_versions(config).map_err(FrumError::IoError)?,
+                    )
+                } else {
+                    (list_remote_versions(config)?, vec![])
+                };
+                let candidates = versions
+                    .iter()
+                    .map(|x| &x.version)
+                    .chain(installed_versions.iter());
+                let version =
+                    match current_version.to_version(candidates.clone(), self.include_prerelease) {
+                        Some(version) => version.clone(),
+                        None if current_version.to_version(candidates, true).is_some() => {
+                            return Err(FrumError::OnlyPrereleaseFound {
+                                version: current_version,
+                            })
+                        }
+                        None => {
+                            return Err(FrumError::VersionNotFound {
+                                version: current_version,
+                            })
+                        }
+                    };
+                remote_versions = Some(versions);
+                version
             }
         };
-        let installations_dir = config.versions_dir();
-        let installation_dir = PathBuf::from(&installations_dir).join(version.to_string());
+        let _lock = lock_new_installation(config, &version)?;
 
-        if installation_dir.exists() {
-            return Err(FrumError::VersionAlreadyInstalled {
-                path: installation_dir,
-            });
-        }
+        let checksums = if self.skip_checksum {
+            Format::SUPPORTED
+                .iter()
+                .map(|format| (*format, None))
+                .collect()
+        } else {
+            let versions = match remote_versions {
+                Some(versions) => versions,
+                None => list_remote_versions(config)?,
+            };
+            find_checksums(&versions, &version)?
+        };
+        let candidates = checksums
+            .into_iter()
+            .map(|(format, sha256)| Download {
+                url: package_url(config.ruby_build_mirror.clone(), &version, format),
+                file_name: archive(&version, format),
+                sha256,
+            })
+            .collect();
 
-        let url = package_url(config.ruby_build_mirror.clone(), &version);
-        outln!(config#Info, "{} Downloading {}", "==>".green(), format!("{}", url).green());
-        let response = reqwest::blocking::get(url)?;
-        if response.status() == 404 {
-            return Err(FrumError::VersionNotFound {
-                version: current_version,
-            });
-        }
+        let cached_archive = match find_or_download_archive(config, candidates)? {
+            Some(cached_archive) => cached_archive,
+            None if config.offline => return Err(FrumError::ArchiveNotCached { version }),
+            None => {
+                return Err(FrumError::VersionNotFound {
+                    version: current_version,
+                })
+            }
+        };
+
+        let temp_dir = extract_archive(config, &cached_archive.path)?;
+        self.build_and_install(config, &version, &source_dir(&temp_dir)?)
+    }
 
-        outln!(config#Info, "{} Extracting {}", "==>".green(), archive(&version).green());
-        let temp_installations_dir = installations_dir.join(".downloads");
-        std::fs::create_dir_all(&temp_installations_dir).map_err(FrumError::IoError)?;
-        let temp_dir = tempfile::TempDir::new_in(&temp_installations_dir)
-            .expect("Can't generate a temp directory");
-        extract_archive_into(&temp_dir, response)?;
+    /// Installs another Ruby implementation from its upstream release.
+    fn install_engine_from_mirror(
+        &self,
+        config: &FrumConfig,
+        engine_version: EngineVersion,
+    ) -> Result<(), FrumError> {
+        let version = Version::Engine(engine_version.clone());
+        let (url, file_name) = engine_version
+            .engine
+            .package(&engine_version)
+            .ok_or_else(|| FrumError::UnsupportedPlatform {
+                version: version.clone(),
+            })?;
+        let _lock = lock_new_installation(config, &version)?;
 
-        outln!(config#Info, "{} Building {}", "==>".green(), format!("Ruby {}", current_version).green());
-        let installed_directory = std::fs::read_dir(&temp_dir)
-            .map_err(FrumError::IoError)?
-            .next()
-            .ok_or(FrumError::TarIsEmpty)?
-            .map_err(FrumError::IoError)?;
-        let installed_directory = installed_directory.path();
-        build_package(
-            &installed_directory,
-            &installation_dir,
-            &self.configure_opts,
-        )?;
+        let sha256 = if self.skip_checksum || config.offline {
+            None
+        } else {
+            engine_checksum(&version, engine_version.engine, &url)?
+        };
+        let download = Download {
+            url,
+            file_name,
+            sha256,
+        };
+        let cached_archive = match find_or_download_archive(config, vec![download])? {
+            Some(cached_archive) => cached_archive,
+            None if config.offline => return Err(FrumError::ArchiveNotCached { version }),
+            None => {
+                return Err(FrumError::VersionNotFound {
+                    version: InputVersion::Full(version),
+                })
+            }
+        };
+
+        let temp_dir = extract_archive(config, &cached_archive.path)?;
+        self.build_and_install(config, &version, &source_dir(&temp_dir)?)
+    }
+
+    fn install_from_file(&self, config: &FrumConfig, path: &Path) -> Result<(), FrumError> {
+        // A version known from the name is checked before extracting the whole source tree.
+        let named = self
+            .name
+            .clone()
+            .or_else(|| version_from_archive_name(path))
+            .map(|version| lock_new_installation(config, &version).map(|lock| (version, lock)))
+            .transpose()?;
+        let temp_dir = extract_archive(config, path)?;
+        let source_dir = source_dir(&temp_dir)?;
+        let (version, _lock) = match named {
+            Some(named) => named,
+            None => {
+                let version = version_from_source(&source_dir)?;
+                let lock = lock_new_installation(config, &version)?;
+                (version, lock)
+            }
+        };
+        self.build_and_install(config, &version, &source_dir)
+    }
+
+    fn install_from_dir(&self, config: &FrumConfig, path: &Path) -> Result<(), FrumError> {
+        let source_dir = path
+            .canonicalize()
+            .map_err(|_| FrumError::SourceDirNotFound {
+                path: path.to_path_buf(),
+            })?;
+        let version = match self.name.clone() {
+            Some(version) => version,
+            None => version_from_source(&source_dir)?,
+        };
+        let _lock = lock_new_installation(config, &version)?;
+        self.build_and_install(config, &version, &source_dir)
+    }
+
+    /// Builds the sources in `source_dir` and moves the result into the versions directory.
+    /// The caller must hold the installation lock of `version`.
+    fn build_and_install(
+        &self,
+        config: &FrumConfig,
+        version: &Version,
+        source_dir: &Path,
+    ) -> Result<(), FrumError> {
+        let name = match version {
+            Version::Engine(_) => version.to_string(),
+            _ => format!("Ruby {}", version),
+        };
+        outln!(config#Info, "{} Building {}", "==>".green(), name.green());
+        let installation_dir = config.versions_dir().join(version.to_string());
+        let mut log = BuildLog::create(config, &version.to_string()).map_err(FrumError::IoError)?;
+        let staging_dir = staging_dir(config).join(version.to_string());
+        let result = match version {
+            Version::Engine(version) if version.engine.is_prebuilt() => install_prebuilt(
+                version.engine,
+                source_dir,
+                &staging_dir,
+                &mut log,
+                config,
+                self.verbose,
+            ),
+            Version::Engine(_) => {
+                build_mruby(source_dir, &staging_dir, &mut log, config, self.verbose)
+            }
+            _ => build_package(
+                source_dir,
+                &installation_dir,
+                &staging_dir,
+                &self.configure_opts,
+                &mut log,
+                config,
+                self.verbose,
+            ),
+        };
+        let staged_installation_dir = match result {
+            Ok(staged_installation_dir) => staged_installation_dir,
+            Err(err) => {
+                std::fs::remove_dir_all(&staging_dir).ok();
+                return Err(err);
+            }
+        };
+        debug!(
+            "move {} to {}",
+            staged_installation_dir.display(),
+            installation_dir.display()
+        );
+        std::fs::rename(&staged_installation_dir, &installation_dir).map_err(FrumError::IoError)?;
+        std::fs::remove_dir_all(&staging_dir).map_err(FrumError::IoError)?;
 
-        if !config.default_version_dir().exists() {
-            debug!("Use {} as the default version", current_version);
-            create_alias(&config, "default", &version).map_err(FrumError::IoError)?;
+        if create_alias_if_missing(config, "default", version).map_err(FrumError::IoError)? {
+            debug!("Use {} as the default version", version);
+        }
+        if config.shims_dir().exists() {
+            rehash(config)?;
         }
         Ok(())
     }
 }
 
-fn extract_archive_into<P: AsRef<Path>>(
-    path: P,
-    response: reqwest::blocking::Response,
-) -> Result<(), FrumError> {
-    #[cfg(unix)]
-    let extractor = archive::tar_xz::TarXz::new(response);
-    #[cfg(windows)]
-    let extractor = archive::zip::Zip::new(response);
-    extractor
-        .extract_into(path)
-        .map_err(|source| FrumError::ExtractError { source })?;
-    Ok(())
+/// Takes the installation lock of `version` and makes sure it isn't installed yet.
+fn lock_new_installation(config: &FrumConfig, version: &Version) -> Result<FileLock, FrumError> {
+    let lock = lock::lock_installation(config, &version.to_string())?;
+    remove_stale_staging_dirs(config, version).map_err(FrumError::IoError)?;
+    let installation_dir = config.versions_dir().join(version.to_string());
+    if installation_dir.exists() {
+        return Err(FrumError::VersionAlreadyInstalled {
+            path: installation_dir,
+        });
+    }
+    Ok(lock)
 }
 
-fn package_url(mirror_url: Url, version: &Version) -> Url {
+/// Extracts an archive into a temporary directory under the versions directory.
+fn extract_archive(config: &FrumConfig, path: &Path) -> Result<tempfile::TempDir, FrumError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    outln!(config#Info, "{} Extracting {}", "==>".green(), file_name.green());
+    let temp_installations_dir = config.versions_dir().join(".downloads");
+    std::fs::create_dir_all(&temp_installations_dir).map_err(FrumError::IoError)?;
+    let temp_dir = tempfile::TempDir::new_in(&temp_installations_dir)
+        .expect("Can't generate a temp directory");
+    archive::extract_into(
+        std::fs::File::open(path).map_err(FrumError::IoError)?,
+        &temp_dir,
+    )
+    .map_err(|source| FrumError::ExtractError { source })?;
+    Ok(temp_dir)
+}
+
+/// Returns the top-level directory of an extracted archive.
+fn source_dir(temp_dir: &tempfile::TempDir) -> Result<PathBuf, FrumError> {
+    let entry = std::fs::read_dir(temp_dir)
+        .map_err(FrumError::IoError)?
+        .next()
+        .ok_or(FrumError::TarIsEmpty)?
+        .map_err(FrumError::IoError)?;
+    Ok(entry.path())
+}
+
+/// Derives the version from an archive named like `ruby-3.3.0.tar.xz`.
+fn version_from_archive_name(path: &Path) -> Option<Version> {
+    let file_name = path.file_name()?.to_str()?;
+    let stem = Format::ALL
+        .iter()
+        .find_map(|format| file_name.strip_suffix(&format!(".{}", format.extension())))?;
+    match Version::parse(stem.strip_prefix("ruby-")?) {
+        Ok(version @ Version::Ruby(_)) => Some(version),
+        _ => None,
+    }
+}
+
+/// Reads the version from `version.h` in a Ruby source tree. Older sources define
+/// `RUBY_VERSION` directly, newer ones take the major and minor versions from
+/// `include/ruby/version.h`.
+fn version_from_source(source_dir: &Path) -> Result<Version, FrumError> {
+    let cant_detect = || FrumError::CantDetectVersion {
+        path: source_dir.to_path_buf(),
+    };
+    let version_h =
+        std::fs::read_to_string(source_dir.join("version.h")).map_err(|_| cant_detect())?;
+    let defines = c_defines(&version_h);
+    if let Some(version) = defines.get("RUBY_VERSION") {
+        return Version::parse(version.trim_matches('"')).map_err(|_| cant_detect());
+    }
+
+    let api_version_h =
+        std::fs::read_to_string(source_dir.join("include/ruby/version.h")).unwrap_or_default();
+    let api_defines = c_defines(&api_version_h);
+    let number = |name: &str, api_name: &str| {
+        defines
+            .get(name)
+            .filter(|value| value.parse::<u64>().is_ok())
+            .or_else(|| api_defines.get(api_name))
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(cant_detect)
+    };
+    let version = format!(
+        "{}.{}.{}",
+        number("RUBY_VERSION_MAJOR", "RUBY_API_VERSION_MAJOR")?,
+        number("RUBY_VERSION_MINOR", "RUBY_API_VERSION_MINOR")?,
+        number("RUBY_VERSION_TEENY", "RUBY_API_VERSION_TEENY")?,
+    );
+    Version::parse(version).map_err(|_| cant_detect())
+}
+
+fn c_defines(source: &str) -> std::collections::HashMap<&str, &str> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.trim().strip_prefix("#define")?.split_whitespace();
+            Some((words.next()?, words.next()?))
+        })
+        .collect()
+}
+
+fn list_remote_versions(config: &FrumConfig) -> Result<Vec<IndexedRubyVersion>, FrumError> {
+    crate::remote_ruby_index::list(config)
+        .map_err(|source| FrumError::CantListRemoteVersions { source })
+}
+
+/// Returns the supported formats the index lists for `version` with their checksums,
+/// in order of preference.
+fn find_checksums(
+    versions: &[IndexedRubyVersion],
+    version: &Version,
+) -> Result<Vec<(Format, Option<String>)>, FrumError> {
+    let checksums = Format::SUPPORTED
+        .iter()
+        .filter_map(|format| {
+            let archive = archive(version, *format);
+            versions
+                .iter()
+                .find(|x| &x.version == version && x.url.ends_with(&format!("/{}", archive)))
+                .map(|x| (*format, Some(x.sha256.to_lowercase())))
+        })
+        .collect::<Vec<_>>();
+    if checksums.is_empty() {
+        return Err(FrumError::ChecksumNotFound {
+            version: version.clone(),
+        });
+    }
+    Ok(checksums)
+}
+
+/// An archive to download and the name to cache it as.
+struct Download {
+    url: Url,
+    file_name: String,
+    sha256: Option<String>,
+}
+
+/// Returns the first candidate found in the download cache, or downloads the first
+/// one the server has.
+fn find_or_download_archive(
+    config: &FrumConfig,
+    candidates: Vec<Download>,
+) -> Result<Option<CachedArchive>, FrumError> {
+    for download in &candidates {
+        if let Some(cached_archive) =
+            download_cache::find(config, &download.file_name, download.sha256.as_deref())
+        {
+            outln!(config#Info, "{} Using cached {}", "==>".green(), download.file_name.green());
+            return Ok(Some(cached_archive));
+        }
+    }
+    if config.offline {
+        return Ok(None);
+    }
+    for download in candidates {
+        if let Some(cached_archive) = download_archive(config, download)? {
+            return Ok(Some(cached_archive));
+        }
+    }
+    Ok(None)
+}
+
+/// Downloads the archive into the cache. Returns `None` if the server doesn't have it.
+fn download_archive(
+    config: &FrumConfig,
+    download: Download,
+) -> Result<Option<CachedArchive>, FrumError> {
+    let Download {
+        url,
+        file_name,
+        sha256: expected_checksum,
+    } = download;
+    outln!(config#Info, "{} Downloading {}", "==>".green(), format!("{}", url).green());
+    let response = reqwest::blocking::get(url)?;
+    if response.status() == 404 {
+        debug!("{} is not found on the server", file_name);
+        return Ok(None);
+    }
+    let response = response.error_for_status()?;
+
+    let mut file = download_cache::temp_file(config).map_err(FrumError::IoError)?;
+    let mut reader = Sha256Reader::new(response);
+    std::io::copy(&mut reader, &mut file).map_err(FrumError::IoError)?;
+    let actual_checksum = reader.finish().map_err(FrumError::IoError)?;
+    debug!("sha256: {}", actual_checksum);
+    if let Some(expected_checksum) = expected_checksum {
+        if expected_checksum != actual_checksum {
+            return Err(FrumError::ChecksumMismatch {
+                archive: file_name,
+                expected: expected_checksum,
+                actual: actual_checksum,
+            });
+        }
+    }
+    download_cache::store(config, file, &file_name, &actual_checksum)
+        .map(Some)
+        .map_err(FrumError::IoError)
+}
+
+fn package_url(mirror_url: Url, version: &Version, format: Format) -> Url {
     debug!("pakage url");
     Url::parse(&format!(
         "{}/{}/{}",
         mirror_url.as_str().trim_end_matches('/'),
         match version {
-            Version::Semver(version) => format!("{}.{}", version.major, version.minor),
+            Version::Ruby(version) => format!("{}.{}", version.major, version.minor),
             _ => unreachable!(),
         },
-        archive(version),
+        archive(version, format),
     ))
     .unwrap()
 }
 
-#[cfg(unix)]
-fn archive(version: &Version) -> String {
-    format!("ruby-{}.tar.xz", version)
+fn archive(version: &Version, format: Format) -> String {
+    format!("ruby-{}.{}", version, format.extension())
 }
 
-#[cfg(windows)]
-fn archive(version: &Version) -> String {
-    format!("ruby-{}.zip", version)
+/// Fetches the checksum JRuby publishes next to its release archives. The other
+/// engines don't publish checksums, so their archives can't be verified.
+fn engine_checksum(
+    version: &Version,
+    engine: Engine,
+    url: &Url,
+) -> Result<Option<String>, FrumError> {
+    if engine != Engine::Jruby {
+        debug!("{} doesn't publish checksums", engine);
+        return Ok(None);
+    }
+    let checksum_url = Url::parse(&format!("{}.sha256", url)).expect("invalid checksum url");
+    let response = reqwest::blocking::get(checksum_url.clone())?;
+    if response.status() == 404 {
+        return Err(FrumError::EngineChecksumNotFound {
+            version: version.clone(),
+            url: checksum_url.to_string(),
+        });
+    }
+    let checksum = response.error_for_status()?.text()?;
+    match checksum.split_whitespace().next() {
+        Some(checksum) => Ok(Some(checksum.to_lowercase())),
+        None => Err(FrumError::EngineChecksumNotFound {
+            version: version.clone(),
+            url: checksum_url.to_string(),
+        }),
+    }
 }
 
 #[allow(clippy::unnecessary_wraps)]
@@ -178,11 +603,200 @@ fn openssl_dir() -> Result<String, FrumError> {
     return Ok("/usr/local".to_string());
 }
 
+fn staging_dir(config: &FrumConfig) -> PathBuf {
+    config.versions_dir().join(".staging")
+}
+
+/// Removes staging directories left behind by interrupted installations.
+/// Directories of versions being installed by another frum process are kept.
+/// The caller must hold the installation lock of `current_version`.
+fn remove_stale_staging_dirs(
+    config: &FrumConfig,
+    current_version: &Version,
+) -> std::io::Result<()> {
+    let staging_dir = staging_dir(config);
+    if !staging_dir.exists() {
+        return Ok(());
+    }
+    for entry in staging_dir.read_dir()? {
+        let entry = entry?;
+        let version_name = entry.file_name().to_string_lossy().to_string();
+        let _lock = if version_name == current_version.to_string() {
+            None
+        } else {
+            match lock::try_lock_installation(config, &version_name)? {
+                Some(lock) => Some(lock),
+                None => continue,
+            }
+        };
+        debug!("remove stale staging directory {}", entry.path().display());
+        std::fs::remove_dir_all(entry.path())?;
+    }
+    Ok(())
+}
+
+/// Runs one phase of a build, logging its output.
+fn run_build_phase(
+    phase: &str,
+    command: &mut Command,
+    log: &mut BuildLog,
+    config: &FrumConfig,
+    verbose: bool,
+) -> Result<(), FrumError> {
+    let mut progress = Progress::start(config, phase, verbose);
+    let status = log
+        .run(phase, command, &mut progress)
+        .map_err(FrumError::IoError)?;
+    progress.finish();
+    if !status.success() {
+        return Err(FrumError::CantBuildRuby {
+            phase: phase.to_string(),
+            log_path: log.path().to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+/// Copies `from` into `to`, keeping symlinks as they are.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in from.read_dir()? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::copy(from, to).map(|_| ())
+}
+
+/// Links `bin/<name>` to `bin/<target>` so that the usual Ruby commands work.
+fn link_executable(installed_dir: &Path, name: &str, target: &str) -> std::io::Result<()> {
+    let bin_dir = installed_dir.join("bin");
+    if !bin_dir.join(target).exists() || bin_dir.join(name).exists() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, bin_dir.join(name));
+    #[cfg(windows)]
+    return std::fs::copy(bin_dir.join(target), bin_dir.join(name)).map(|_| ());
+}
+
+/// Stages a prebuilt JRuby or TruffleRuby release and returns the staged installation
+/// once it passes a smoke test.
+fn install_prebuilt(
+    engine: Engine,
+    current_dir: &Path,
+    staging_dir: &Path,
+    log: &mut BuildLog,
+    config: &FrumConfig,
+    verbose: bool,
+) -> Result<PathBuf, FrumError> {
+    let staged_installed_dir = staging_dir.join("install");
+    debug!(
+        "copy {} to {}",
+        current_dir.display(),
+        staged_installed_dir.display()
+    );
+    copy_dir(current_dir, &staged_installed_dir).map_err(FrumError::IoError)?;
+    if engine == Engine::Jruby {
+        link_executable(&staged_installed_dir, "ruby", "jruby").map_err(FrumError::IoError)?;
+    }
+    let post_install_hook = staged_installed_dir.join("lib/truffle/post_install_hook.sh");
+    if engine == Engine::Truffleruby && post_install_hook.exists() {
+        run_build_phase(
+            "post install hook",
+            Command::new("sh")
+                .arg(&post_install_hook)
+                .current_dir(&staged_installed_dir),
+            log,
+            config,
+            verbose,
+        )?;
+    }
+    run_build_phase(
+        "smoke test",
+        Command::new(staged_installed_dir.join("bin").join("ruby")).arg("--version"),
+        log,
+        config,
+        verbose,
+    )?;
+    Ok(staged_installed_dir)
+}
+
+/// Builds mruby with rake and stages its executables, libraries and headers.
+fn build_mruby(
+    current_dir: &Path,
+    staging_dir: &Path,
+    log: &mut BuildLog,
+    config: &FrumConfig,
+    verbose: bool,
+) -> Result<PathBuf, FrumError> {
+    debug!("rake in {}", current_dir.display());
+    run_build_phase(
+        "rake",
+        Command::new("rake").current_dir(current_dir),
+        log,
+        config,
+        verbose,
+    )?;
+
+    let staged_installed_dir = staging_dir.join("install");
+    let build_dir = current_dir.join("build").join("host");
+    for dir in &["bin", "lib"] {
+        if build_dir.join(dir).exists() {
+            copy_dir(&build_dir.join(dir), &staged_installed_dir.join(dir))
+                .map_err(FrumError::IoError)?;
+        }
+    }
+    copy_dir(
+        &current_dir.join("include"),
+        &staged_installed_dir.join("include"),
+    )
+    .map_err(FrumError::IoError)?;
+    link_executable(&staged_installed_dir, "ruby", "mruby").map_err(FrumError::IoError)?;
+    link_executable(&staged_installed_dir, "irb", "mirb").map_err(FrumError::IoError)?;
+
+    run_build_phase(
+        "smoke test",
+        Command::new(staged_installed_dir.join("bin").join("mruby")).arg("--version"),
+        log,
+        config,
+        verbose,
+    )?;
+    Ok(staged_installed_dir)
+}
+
+/// Builds Ruby for `installed_dir`, installs it under `staging_dir` with
+/// `DESTDIR` and returns the staged copy of `installed_dir` once it passes a
+/// smoke test.
 fn build_package(
     current_dir: &Path,
     installed_dir: &Path,
+    staging_dir: &Path,
     configure_opts: &[String],
-) -> Result<(), FrumError> {
+    log: &mut BuildLog,
+    config: &FrumConfig,
+    verbose: bool,
+) -> Result<PathBuf, FrumError> {
+    let mut run_build_phase =
+        |phase: &str, command: &mut Command| run_build_phase(phase, command, log, config, verbose);
+
     debug!("./configure {}", configure_opts.join(" "));
     let mut command = Command::new("sh");
     command
@@ -198,48 +812,39 @@ fn build_package(
         command.arg(format!("--with-openssl-dir={}", openssl_dir()?));
     }
 
-    let configure = command
-        .current_dir(&current_dir)
-        .output()
-        .map_err(FrumError::IoError)?;
-    if !configure.status.success() {
-        return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "configure failed: {}",
-                String::from_utf8_lossy(&configure.stderr).to_string()
-            ),
-        });
-    };
+    run_build_phase("configure", command.current_dir(&current_dir))?;
     debug!("make -j {}", num_cpus::get().to_string());
-    let make = Command::new("make")
-        .arg("-j")
-        .arg(num_cpus::get().to_string())
-        .current_dir(&current_dir)
-        .output()
-        .map_err(FrumError::IoError)?;
-    if !make.status.success() {
-        return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "make failed: {}",
-                String::from_utf8_lossy(&make.stderr).to_string()
-            ),
-        });
-    };
-    debug!("make install");
-    let make_install = Command::new("make")
-        .arg("install")
-        .current_dir(&current_dir)
-        .output()
-        .map_err(FrumError::IoError)?;
-    if !make_install.status.success() {
-        return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "make install: {}",
-                String::from_utf8_lossy(&make_install.stderr).to_string()
-            ),
-        });
-    };
-    Ok(())
+    run_build_phase(
+        "make",
+        Command::new("make")
+            .arg("-j")
+            .arg(num_cpus::get().to_string())
+            .current_dir(&current_dir),
+    )?;
+    debug!("make install DESTDIR={}", staging_dir.display());
+    run_build_phase(
+        "make install",
+        Command::new("make")
+            .arg("install")
+            .arg(format!("DESTDIR={}", staging_dir.to_str().unwrap()))
+            .current_dir(&current_dir),
+    )?;
+
+    let staged_installed_dir = staging_dir.join(
+        installed_dir
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect::<PathBuf>(),
+    );
+    let staged_lib_dir = staged_installed_dir.join("lib");
+    run_build_phase(
+        "smoke test",
+        Command::new(staged_installed_dir.join("bin").join("ruby"))
+            .arg("-v")
+            .env("LD_LIBRARY_PATH", &staged_lib_dir)
+            .env("DYLD_LIBRARY_PATH", &staged_lib_dir),
+    )?;
+    Ok(staged_installed_dir)
 }
 
 #[cfg(test)]
@@ -250,6 +855,158 @@ mod tests {
     use crate::version::Version;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_find_checksums() {
+        let versions = ["tar.gz", "tar.xz", "zip"]
+            .iter()
+            .map(|ext| IndexedRubyVersion {
+                version: Version::parse("2.7.0").unwrap(),
+                url: format!(
+                    "https://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.0.{}",
+                    ext
+                ),
+                sha1: String::new(),
+                sha256: format!("{}-SHA256", ext),
+                sha521: String::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let expected = if cfg!(windows) {
+            vec![(Format::Zip, Some("zip-sha256".to_string()))]
+        } else {
+            vec![
+                (Format::TarXz, Some("tar.xz-sha256".to_string())),
+                (Format::TarGz, Some("tar.gz-sha256".to_string())),
+            ]
+        };
+        assert_eq!(
+            find_checksums(&versions, &Version::parse("2.7.0").unwrap()).unwrap(),
+            expected
+        );
+        assert!(matches!(
+            find_checksums(&versions, &Version::parse("2.6.4").unwrap()),
+            Err(FrumError::ChecksumNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_version_from_archive_name() {
+        assert_eq!(
+            version_from_archive_name(Path::new("/tmp/ruby-3.3.0.tar.xz")),
+            Some(Version::parse("3.3.0").unwrap())
+        );
+        assert_eq!(
+            version_from_archive_name(Path::new("ruby-2.7.0-preview1.tar.bz2")),
+            Some(Version::parse("2.7.0-preview1").unwrap())
+        );
+        assert_eq!(
+            version_from_archive_name(Path::new("ruby-src.tar.gz")),
+            None
+        );
+        assert_eq!(version_from_archive_name(Path::new("patched.tar.gz")), None);
+        assert_eq!(version_from_archive_name(Path::new("ruby-3.3.0.tgz")), None);
+    }
+
+    #[test]
+    fn test_version_from_source() {
+        let source_dir = tempdir().unwrap();
+        std::fs::write(
+            source_dir.path().join("version.h"),
+            "#define RUBY_VERSION \"2.6.4\"\n#define RUBY_PATCHLEVEL 104\n",
+        )
+        .unwrap();
+        assert_eq!(
+            version_from_source(source_dir.path()).unwrap(),
+            Version::parse("2.6.4").unwrap()
+        );
+
+        let source_dir = tempdir().unwrap();
+        std::fs::create_dir_all(source_dir.path().join("include/ruby")).unwrap();
+        std::fs::write(
+            source_dir.path().join("version.h"),
+            "#define RUBY_VERSION_TEENY 1\n#define RUBY_VERSION_MAJOR RUBY_API_VERSION_MAJOR\n",
+        )
+        .unwrap();
+        std::fs::write(
+            source_dir.path().join("include/ruby/version.h"),
+            "#define RUBY_API_VERSION_MAJOR 3\n#define RUBY_API_VERSION_MINOR 3\n",
+        )
+        .unwrap();
+        assert_eq!(
+            version_from_source(source_dir.path()).unwrap(),
+            Version::parse("3.3.1").unwrap()
+        );
+
+        let source_dir = tempdir().unwrap();
+        assert!(matches!(
+            version_from_source(source_dir.path()),
+            Err(FrumError::CantDetectVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_remove_stale_staging_dirs() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        for version in &["2.7.0", "2.6.4", "3.0.0"] {
+            std::fs::create_dir_all(staging_dir(&config).join(version).join("bin")).unwrap();
+        }
+        let _lock = lock::try_lock_installation(&config, "3.0.0").unwrap();
+
+        remove_stale_staging_dirs(&config, &Version::parse("2.7.0").unwrap()).unwrap();
+
+        assert!(!staging_dir(&config).join("2.7.0").exists());
+        assert!(!staging_dir(&config).join("2.6.4").exists());
+        assert!(staging_dir(&config).join("3.0.0").exists());
+    }
+
+    #[test]
+    fn test_install_from_file_rejects_version() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let result = Install {
+            version: Some(InputVersion::Full(Version::parse("3.3.0").unwrap())),
+            configure_opts: vec![],
+            local_source: Some(LocalSource::File("ruby-3.3.0.tar.xz".into())),
+            name: None,
+            skip_checksum: false,
+            verbose: false,
+            include_prerelease: false,
+        }
+        .apply(&config);
+        assert!(matches!(
+            result,
+            Err(FrumError::VersionWithLocalSource { .. })
+        ));
+    }
+
+    #[test]
+    fn test_install_from_file_checks_name_before_extracting() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        std::fs::create_dir_all(config.versions_dir().join("3.3.0-custom")).unwrap();
+        let result = Install {
+            version: None,
+            configure_opts: vec![],
+            local_source: Some(LocalSource::File("missing.tar.xz".into())),
+            name: Some(Version::parse("3.3.0-custom").unwrap()),
+            skip_checksum: false,
+            verbose: false,
+            include_prerelease: false,
+        }
+        .apply(&config);
+        assert!(matches!(
+            result,
+            Err(FrumError::VersionAlreadyInstalled { .. })
+        ));
+    }
+
     #[test]
     fn test_install_second_version() {
         let config = FrumConfig {
@@ -258,19 +1015,25 @@ mod tests {
         };
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.7.0").unwrap(),
-            ))),
+            version: Some(InputVersion::Full(Version::parse("2.7.0").unwrap())),
             configure_opts: vec![],
+            local_source: None,
+            name: None,
+            skip_checksum: false,
+            verbose: false,
+            include_prerelease: false,
         }
         .apply(&config)
         .expect("Can't install 2.7.0");
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
             configure_opts: vec![],
+            local_source: None,
+            name: None,
+            skip_checksum: false,
+            verbose: false,
+            include_prerelease: false,
         }
         .apply(&config)
         .expect("Can't install 2.6.4");
@@ -292,10 +1055,13 @@ mod tests {
         };
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
             configure_opts: vec![],
+            local_source: None,
+            name: None,
+            skip_checksum: false,
+            verbose: false,
+            include_prerelease: false,
         }
         .apply(&config)
         .expect("Can't install");
@@ -4,19 +4,26 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum FrumError {
     #[error(transparent)]
-    HttpError(#[from] reqwest::Error),
+    IndexError(#[from] crate::remote_ruby_index::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
 
-pub struct InstallList {}
+pub struct InstallList {
+    pub include_prerelease: bool,
+}
 
 impl crate::command::Command for InstallList {
     type Error = FrumError;
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), FrumError> {
-        let versions = crate::remote_ruby_index::list(&config.ruby_build_mirror)?;
-        let versions = versions.into_iter().map(|v| v.version).sorted().dedup();
+        let versions = crate::remote_ruby_index::list(config)?;
+        let versions = versions
+            .into_iter()
+            .map(|v| v.version)
+            .filter(|version| self.include_prerelease || !version.is_prerelease())
+            .sorted()
+            .dedup();
         for version in versions {
             crate::outln!(config#Info, "{}", version);
         }
@@ -1,7 +1,10 @@
 use crate::input_version::InputVersion;
 use crate::symlink::{create_symlink_dir, remove_symlink_dir};
-use crate::version_file::get_user_version_for_directory;
+use crate::version_file::{
+    get_user_version_for_directory, version_file_candidates, RUBY_VERSION_FILE,
+};
 use log::debug;
+use std::path::Path;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,8 +13,6 @@ pub enum FrumError {
     HttpError(#[from] reqwest::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
-    #[error("We can't find the necessary environment variables to replace the Ruby version.")]
-    FrumPathNotFound,
     #[error("Requested version {version} is not currently installed")]
     VersionNotFound { version: InputVersion },
     #[error("Can't find version in dotfiles. Please provide a version manually to the command.")]
@@ -20,54 +21,151 @@ pub enum FrumError {
 
 pub struct Local {
     pub version: Option<InputVersion>,
+    pub unset: bool,
+    pub no_write: bool,
+    /// Skips switching while the version of the directory can't have changed since
+    /// the last switch, for the hook that runs on every prompt.
+    pub if_changed: bool,
 }
 
 impl crate::command::Command for Local {
     type Error = FrumError;
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
-        debug!("log level {:?}", config.log_level);
-        let current_version = match self.version.clone().ok_or_else(|| {
-            match get_user_version_for_directory(std::env::current_dir().unwrap()) {
-                Some(version) => Ok(version),
-                None => {
-                    replace_symlink(
-                        &config.default_version_dir(),
-                        &config
-                            .frum_path
-                            .clone()
-                            .ok_or(FrumError::FrumPathNotFound)?,
-                    )?;
+        self.apply_in(
+            config,
+            &std::env::current_dir().map_err(FrumError::IoError)?,
+        )
+    }
+}
 
-                    Err(FrumError::CantInferVersion)
+impl Local {
+    /// Switches to the version of `dir`. A version given explicitly is recorded in
+    /// `dir/.ruby-version` unless `no_write` is set.
+    fn apply_in(&self, config: &crate::config::FrumConfig, dir: &Path) -> Result<(), FrumError> {
+        debug!("log level {:?}", config.log_level);
+        let version_file = dir.join(RUBY_VERSION_FILE);
+        if self.unset {
+            match std::fs::remove_file(&version_file) {
+                Ok(()) => debug!("removed {}", version_file.display()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(FrumError::IoError(err)),
+            }
+        }
+        // Without a multishell path (e.g. with shims) only the version file is written.
+        let frum_path = config.frum_path.as_ref();
+        let stamp = version_stamp(config, dir);
+        let unchanged = match frum_path {
+            Some(frum_path) if self.if_changed => {
+                frum_path.exists()
+                    && std::fs::read_to_string(stamp_path(frum_path)).ok().as_ref() == Some(&stamp)
+            }
+            _ => false,
+        };
+        if unchanged {
+            debug!("the version of {} is unchanged", dir.display());
+            return Ok(());
+        }
+        let current_version = match self
+            .version
+            .clone()
+            .or_else(|| get_user_version_for_directory(config, dir.to_path_buf()))
+        {
+            Some(version) => version,
+            None => {
+                if let Some(frum_path) = frum_path {
+                    replace_symlink(&config.default_version_dir(), frum_path)?;
+                    write_stamp(frum_path, &version_stamp(config, dir));
                 }
+                return if self.unset {
+                    Ok(())
+                } else {
+                    Err(FrumError::CantInferVersion)
+                };
             }
-        }) {
-            Ok(version) => version,
-            Err(result) => result?,
         };
         debug!("Use {} as the current version", current_version);
-        if !&config
-            .versions_dir()
-            .join(current_version.to_string())
-            .exists()
+        let version = match current_version
+            .to_installed_version(config)
+            .map_err(FrumError::IoError)?
         {
+            Some(version) => version,
+            None => {
+                return Err(FrumError::VersionNotFound {
+                    version: current_version,
+                })
+            }
+        };
+        let version_dir = config.versions_dir().join(version.to_string());
+        if !version_dir.exists() {
             return Err(FrumError::VersionNotFound {
                 version: current_version,
             });
         }
-        replace_symlink(
-            &config.versions_dir().join(current_version.to_string()),
-            &config
-                .frum_path
-                .clone()
-                .ok_or(FrumError::FrumPathNotFound)?,
-        )
-        .map_err(FrumError::IoError)?;
+        if let Some(frum_path) = frum_path {
+            replace_symlink(&version_dir, frum_path).map_err(FrumError::IoError)?;
+        } else {
+            debug!("no multishell path, so only the version file is written");
+        }
+        let stamp = if self.version.is_some() && !self.no_write {
+            debug!("write {} to {}", version, version_file.display());
+            std::fs::write(&version_file, format!("{}\n", version)).map_err(FrumError::IoError)?;
+            version_stamp(config, dir)
+        } else {
+            stamp
+        };
+        if let Some(frum_path) = frum_path {
+            write_stamp(frum_path, &stamp);
+        }
         Ok(())
     }
 }
 
+/// Identifies where the version of `dir` comes from: `$FRUM_VERSION` or the metadata of
+/// the version files that exist, plus the metadata of the versions directory. Runs on
+/// every prompt, so the version files are only stat'ed and never parsed.
+fn version_stamp(config: &crate::config::FrumConfig, dir: &Path) -> String {
+    let source = match &config.env_version {
+        Some(version) => format!("env {}\n", version),
+        None => version_file_candidates(config, dir.to_path_buf())
+            .into_iter()
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok().filter(|x| x.is_file())?;
+                Some(format!(
+                    "{} {}\n",
+                    path.display(),
+                    metadata_stamp(&metadata)
+                ))
+            })
+            .collect(),
+    };
+    let versions = std::fs::metadata(config.versions_dir())
+        .map(|metadata| metadata_stamp(&metadata))
+        .unwrap_or_default();
+    format!("{}versions {}\n", source, versions)
+}
+
+fn metadata_stamp(metadata: &std::fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    format!("{} {}", modified, metadata.len())
+}
+
+/// The stamp of the last switch is kept next to the symlink of the shell.
+fn stamp_path(frum_path: &Path) -> std::path::PathBuf {
+    frum_path.with_extension("stamp")
+}
+
+fn write_stamp(frum_path: &Path, stamp: &str) {
+    if let Err(err) = std::fs::write(stamp_path(frum_path), stamp) {
+        debug!("can't write the version stamp: {}", err);
+    }
+}
+
 fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
     let symlink_deletion_result = remove_symlink_dir(&to);
     match create_symlink_dir(&from, &to) {
@@ -78,12 +176,14 @@ fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Res
 
 #[cfg(test)]
 mod tests {
-    use super::{FrumError, Local};
+    use super::{version_stamp, FrumError, Local};
     use crate::command::Command;
+    use crate::config::tests::config_with_versions;
     use crate::config::FrumConfig;
     use crate::input_version::InputVersion;
     use crate::version::Version;
     use std::fs::File;
+    use std::str::FromStr;
     use tempfile::tempdir;
 
     #[test]
@@ -102,15 +202,16 @@ mod tests {
         File::create(dir_path.join("ruby")).unwrap();
 
         crate::commands::global::Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.6.4").unwrap())),
+            version: InputVersion::Full(Version::parse("2.6.4").unwrap()),
         }
         .apply(&config)
         .unwrap();
 
         Local {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            unset: false,
+            no_write: true,
+            if_changed: false,
         }
         .apply(&config)
         .expect("failed to install");
@@ -130,9 +231,10 @@ mod tests {
             ..FrumConfig::default()
         };
         let result = Local {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            unset: false,
+            no_write: false,
+            if_changed: false,
         }
         .apply(&config);
         assert!(matches!(result, Err(FrumError::VersionNotFound { .. })));
@@ -150,7 +252,164 @@ mod tests {
             ..FrumConfig::default()
         };
         std::env::set_current_dir(std::env::temp_dir()).unwrap();
-        let result = Local { version: None }.apply(&config);
+        let result = Local {
+            version: None,
+            unset: false,
+            no_write: false,
+            if_changed: false,
+        }
+        .apply(&config);
         assert!(matches!(result, Err(FrumError::CantInferVersion)));
     }
+
+    #[test]
+    fn test_local_writes_version_file() {
+        let config = config_with_versions(&["2.6.4", "2.7.0"]);
+        let project = tempdir().unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.6.4\n").unwrap();
+
+        Local {
+            version: Some(InputVersion::from_str("2.7").unwrap()),
+            unset: false,
+            no_write: false,
+            if_changed: false,
+        }
+        .apply_in(&config, project.path())
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(project.path().join(".ruby-version")).unwrap(),
+            "2.7.0\n"
+        );
+        assert_eq!(
+            std::fs::read_link(config.frum_path.as_ref().unwrap()).unwrap(),
+            config.versions_dir().join("2.7.0")
+        );
+
+        Local {
+            version: Some(InputVersion::from_str("2.6.4").unwrap()),
+            unset: false,
+            no_write: true,
+            if_changed: false,
+        }
+        .apply_in(&config, project.path())
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(project.path().join(".ruby-version")).unwrap(),
+            "2.7.0\n"
+        );
+        assert_eq!(
+            std::fs::read_link(config.frum_path.as_ref().unwrap()).unwrap(),
+            config.versions_dir().join("2.6.4")
+        );
+    }
+
+    #[test]
+    fn test_local_does_not_write_missing_version() {
+        let config = config_with_versions(&[]);
+        let project = tempdir().unwrap();
+        let result = Local {
+            version: Some(InputVersion::from_str("2.7.0").unwrap()),
+            unset: false,
+            no_write: false,
+            if_changed: false,
+        }
+        .apply_in(&config, project.path());
+        assert!(matches!(result, Err(FrumError::VersionNotFound { .. })));
+        assert!(!project.path().join(".ruby-version").exists());
+    }
+
+    #[test]
+    fn test_local_without_multishell_path() {
+        let config = FrumConfig {
+            frum_path: None,
+            ..config_with_versions(&["3.2.2"])
+        };
+        let project = tempdir().unwrap();
+        Local {
+            version: Some(InputVersion::from_str("3.2.2").unwrap()),
+            unset: false,
+            no_write: false,
+            if_changed: false,
+        }
+        .apply_in(&config, project.path())
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(project.path().join(".ruby-version")).unwrap(),
+            "3.2.2\n"
+        );
+    }
+
+    #[test]
+    fn test_local_if_changed() {
+        let config = config_with_versions(&["2.6.4", "2.7.0"]);
+        let project = tempdir().unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.6.4\n").unwrap();
+        let hook = Local {
+            version: None,
+            unset: false,
+            no_write: false,
+            if_changed: true,
+        };
+        let current = || std::fs::read_link(config.frum_path.as_ref().unwrap()).unwrap();
+
+        hook.apply_in(&config, project.path()).unwrap();
+        assert_eq!(current(), config.versions_dir().join("2.6.4"));
+
+        Local {
+            version: Some(InputVersion::from_str("2.7.0").unwrap()),
+            unset: false,
+            no_write: true,
+            if_changed: false,
+        }
+        .apply_in(&config, project.path())
+        .unwrap();
+        hook.apply_in(&config, project.path()).unwrap();
+        assert_eq!(current(), config.versions_dir().join("2.7.0"));
+
+        std::fs::remove_file(project.path().join(".ruby-version")).unwrap();
+        std::fs::write(project.path().join(".tool-versions"), "ruby 2.6.4\n").unwrap();
+        hook.apply_in(&config, project.path()).unwrap();
+        assert_eq!(current(), config.versions_dir().join("2.6.4"));
+    }
+
+    #[test]
+    fn test_version_stamp() {
+        let config = FrumConfig {
+            env_version: None,
+            ..config_with_versions(&[])
+        };
+        let project = tempdir().unwrap();
+        let sub_dir = project.path().join("sub");
+        std::fs::create_dir(&sub_dir).unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.6.4\n").unwrap();
+
+        let stamp = version_stamp(&config, &sub_dir);
+        std::fs::write(sub_dir.join("README.md"), "").unwrap();
+        assert_eq!(version_stamp(&config, &sub_dir), stamp);
+        // A Gemfile without a version still changes the stamp, since nothing is parsed.
+        std::fs::write(sub_dir.join("Gemfile"), "gem \"rails\"\n").unwrap();
+        assert_ne!(version_stamp(&config, &sub_dir), stamp);
+    }
+
+    #[test]
+    fn test_local_unset() {
+        let config = config_with_versions(&["2.6.4"]);
+        let project = tempdir().unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.6.4\n").unwrap();
+
+        let unset = Local {
+            version: None,
+            unset: true,
+            no_write: false,
+            if_changed: false,
+        };
+        unset.apply_in(&config, project.path()).unwrap();
+        assert!(!project.path().join(".ruby-version").exists());
+        assert_eq!(
+            std::fs::read_link(config.frum_path.as_ref().unwrap()).unwrap(),
+            config.default_version_dir()
+        );
+
+        unset.apply_in(&config, project.path()).unwrap();
+    }
 }
@@ -0,0 +1,36 @@
+use crate::build_log;
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::log::LogLevel;
+use crate::version::Version;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't find a build log for {version}")]
+    LogNotFound { version: InputVersion },
+}
+
+pub struct Logs {
+    pub version: InputVersion,
+}
+
+impl crate::command::Command for Logs {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let path = build_log::latest(config, |name| {
+            Version::parse(name)
+                .map(|version| self.version.matches(&version))
+                .unwrap_or(false)
+        })?
+        .ok_or_else(|| FrumError::LogNotFound {
+            version: self.version.clone(),
+        })?;
+        let mut file = std::fs::File::open(path)?;
+        std::io::copy(&mut file, &mut config.log_level.write(&LogLevel::Info))?;
+        Ok(())
+    }
+}
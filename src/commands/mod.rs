@@ -1,8 +1,15 @@
+pub mod cache;
 pub mod completions;
+pub mod current;
+pub mod exec;
 pub mod global;
 pub mod init;
 pub mod install;
 pub mod install_list;
 pub mod local;
+pub mod logs;
+pub mod rehash;
+pub mod shim;
 pub mod uninstall;
 pub mod versions;
+pub mod which;
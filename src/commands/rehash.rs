@@ -0,0 +1,187 @@
+use crate::commands::which::{bin_dirs, system_path};
+use crate::config::FrumConfig;
+use crate::outln;
+use crate::version::installed_versions;
+use colored::Colorize;
+use log::debug;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
+
+pub struct Rehash {}
+
+impl crate::command::Command for Rehash {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let count = rehash(config)?;
+        outln!(config#Info, "{} Rehashed {} shims in {}", "==>".green(), count, config.shims_dir().display());
+        Ok(())
+    }
+}
+
+/// Links the frum binary into the shims directory under the name of every executable
+/// of the installed versions, and removes the shims of executables that are gone.
+pub fn rehash(config: &FrumConfig) -> Result<usize, FrumError> {
+    let shims_dir = config.shims_dir();
+    std::fs::create_dir_all(&shims_dir)?;
+    let mut names = BTreeSet::new();
+    for version in installed_versions(config)? {
+        for bin_dir in bin_dirs(config, &version) {
+            let entries = match bin_dir.read_dir() {
+                Ok(entries) => entries,
+                Err(err) => {
+                    debug!("skip {}: {}", bin_dir.display(), err);
+                    continue;
+                }
+            };
+            for entry in entries {
+                let path = entry?.path();
+                if !is_executable(&path) {
+                    continue;
+                }
+                if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+    }
+    names.remove(&format!("frum{}", std::env::consts::EXE_SUFFIX));
+
+    for entry in shims_dir.read_dir()? {
+        let entry = entry?;
+        if !names.contains(entry.file_name().to_str().unwrap_or_default()) {
+            debug!("remove stale shim {}", entry.path().display());
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    let frum = frum_executable(config)?;
+    debug!("link shims to {}", frum.display());
+    for name in &names {
+        // Link to a temporary name first so a running shim is never missing.
+        let temp_path = shims_dir.join(format!(".{}.tmp", name));
+        std::fs::remove_file(&temp_path).ok();
+        link_shim(&frum, &temp_path)?;
+        std::fs::rename(&temp_path, shims_dir.join(name))?;
+    }
+    Ok(names.len())
+}
+
+/// The path to link shims to. `current_exe` is fully resolved, e.g. into a versioned
+/// Homebrew Cellar directory that an upgrade removes, so the `frum` on `PATH` that
+/// resolves to the same binary is preferred.
+fn frum_executable(config: &FrumConfig) -> std::io::Result<PathBuf> {
+    Ok(stable_path(std::env::current_exe()?, system_path(config)))
+}
+
+fn stable_path(current_exe: PathBuf, dirs: Vec<PathBuf>) -> PathBuf {
+    let resolved = match current_exe.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(_) => return current_exe,
+    };
+    let file_name = format!("frum{}", std::env::consts::EXE_SUFFIX);
+    dirs.into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.canonicalize().ok().as_ref() == Some(&resolved))
+        .unwrap_or(current_exe)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .map_or(false, |extension| extension == "exe")
+}
+
+#[cfg(unix)]
+fn link_shim(frum: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(frum, path)
+}
+
+#[cfg(windows)]
+fn link_shim(frum: &Path, path: &Path) -> std::io::Result<()> {
+    std::fs::hard_link(frum, path).or_else(|_| std::fs::copy(frum, path).map(|_| ()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn create_file(path: &Path, mode: u32) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_stable_path() {
+        let dir = tempdir().unwrap();
+        let cellar_exe = dir.path().join("Cellar/frum/0.1.2/bin/frum");
+        create_file(&cellar_exe, 0o755);
+        let bin_dir = dir.path().join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::os::unix::fs::symlink(&cellar_exe, bin_dir.join("frum")).unwrap();
+        create_file(&dir.path().join("other/frum"), 0o755);
+
+        assert_eq!(
+            stable_path(
+                cellar_exe.clone(),
+                vec![dir.path().join("other"), bin_dir.clone()]
+            ),
+            bin_dir.join("frum")
+        );
+        assert_eq!(
+            stable_path(cellar_exe.clone(), vec![dir.path().join("other")]),
+            cellar_exe
+        );
+    }
+
+    #[test]
+    fn test_rehash() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        let versions_dir = config.versions_dir();
+        create_file(&versions_dir.join("2.7.0/bin/ruby"), 0o755);
+        create_file(&versions_dir.join("2.7.0/bin/README"), 0o644);
+        create_file(&versions_dir.join("3.2.2/bin/ruby"), 0o755);
+        create_file(&versions_dir.join("3.2.2/bin/rake"), 0o755);
+        create_file(
+            &versions_dir.join("3.2.2/lib/ruby/gems/3.2.0/bin/rails"),
+            0o755,
+        );
+        create_file(&config.shims_dir().join("irb"), 0o755);
+
+        assert_eq!(rehash(&config).unwrap(), 3);
+        let mut shims = config
+            .shims_dir()
+            .read_dir()
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        shims.sort();
+        assert_eq!(shims, vec!["rails", "rake", "ruby"]);
+        assert_eq!(
+            std::fs::read_link(config.shims_dir().join("ruby")).unwrap(),
+            std::env::current_exe().unwrap()
+        );
+    }
+}
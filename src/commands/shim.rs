@@ -0,0 +1,93 @@
+use crate::commands::exec::{build_command, resolve_version, run, FrumError as ExecError};
+use crate::commands::which::locate;
+use crate::config::FrumConfig;
+use crate::version::Version;
+use log::debug;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Exec(#[from] ExecError),
+    #[error(transparent)]
+    Which(#[from] crate::commands::which::FrumError),
+}
+
+/// An executable of the current version, invoked through its shim.
+pub struct Shim {
+    pub name: String,
+    pub args: Vec<OsString>,
+}
+
+impl Shim {
+    /// Returns the shim that `args` invoke, or `None` when frum itself is invoked.
+    pub fn from_args(
+        config: &FrumConfig,
+        mut args: impl Iterator<Item = OsString>,
+    ) -> Option<Self> {
+        let argv0 = PathBuf::from(args.next()?);
+        let file_name = argv0.file_name()?.to_str()?;
+        let name = file_name
+            .strip_suffix(std::env::consts::EXE_SUFFIX)
+            .unwrap_or(file_name)
+            .to_string();
+        if name == "frum" || !shim_path(config, &name).exists() {
+            return None;
+        }
+        Some(Self {
+            name,
+            args: args.collect(),
+        })
+    }
+}
+
+impl crate::command::Command for Shim {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        // Without any version to use, fall back to the executables on `PATH`.
+        let version = match resolve_version(config, None) {
+            Err(ExecError::CantInferVersion) => Version::System,
+            version => version?,
+        };
+        debug!("shim {} with Ruby {}", self.name, version);
+        let program = locate(config, &version, &self.name)?;
+        let mut command = build_command(config, &version, &program, &self.args)?;
+        run(&mut command)?;
+        Ok(())
+    }
+}
+
+fn shim_path(config: &FrumConfig, name: &str) -> PathBuf {
+    config
+        .shims_dir()
+        .join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_shim_from_args() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        std::fs::create_dir_all(config.shims_dir()).unwrap();
+        std::fs::write(shim_path(&config, "ruby"), "").unwrap();
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        let shim = Shim::from_args(&config, args(&["/shims/ruby", "-v"]).into_iter()).unwrap();
+        assert_eq!(shim.name, "ruby");
+        assert_eq!(shim.args, args(&["-v"]));
+        assert!(Shim::from_args(&config, args(&["rake"]).into_iter()).is_none());
+        assert!(Shim::from_args(&config, args(&["ruby3.2"]).into_iter()).is_none());
+        assert!(Shim::from_args(&config, args(&["/usr/bin/frum", "ruby"]).into_iter()).is_none());
+    }
+}
@@ -1,8 +1,9 @@
+use crate::commands::rehash::rehash;
 use crate::config::FrumConfig;
 use crate::input_version::InputVersion;
 use crate::outln;
 use crate::symlink::remove_symlink_dir;
-use crate::version::Version;
+use crate::version::{installed_versions, Version};
 use anyhow::Result;
 use colored::Colorize;
 use log::debug;
@@ -22,10 +23,15 @@ pub enum FrumError {
     FromUtf8Error(#[from] std::string::FromUtf8Error),
     #[error("Can't find version: {version}")]
     VersionNotFound { version: InputVersion },
+    #[error("{version} matches multiple installed versions: {matching_versions}. Please provide the exact version to uninstall.")]
+    AmbiguousVersion {
+        version: InputVersion,
+        matching_versions: String,
+    },
     #[error("The requested version is not installable: {version}")]
     NotInstallableVersion { version: Version },
-    #[error("We can't find the necessary environment variables to replace the Ruby version.")]
-    FrumPathNotFound,
+    #[error(transparent)]
+    RehashError(#[from] crate::commands::rehash::FrumError),
 }
 
 pub struct Uninstall {
@@ -37,14 +43,33 @@ impl crate::command::Command for Uninstall {
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
         let current_version = self.version.clone();
-        let version = match current_version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
-            InputVersion::Full(Version::System) => {
-                return Err(FrumError::NotInstallableVersion {
-                    version: Version::System,
+        if let InputVersion::Full(Version::System) = current_version {
+            return Err(FrumError::NotInstallableVersion {
+                version: Version::System,
+            });
+        }
+        // Unlike `local`, a range must not pick one of several versions to delete.
+        let mut matching_versions = installed_versions(config)?
+            .into_iter()
+            .filter(|version| current_version.matches(version))
+            .collect::<Vec<_>>();
+        let version = match matching_versions.len() {
+            0 => {
+                return Err(FrumError::VersionNotFound {
+                    version: current_version,
+                })
+            }
+            1 => matching_versions.remove(0),
+            _ => {
+                return Err(FrumError::AmbiguousVersion {
+                    version: current_version,
+                    matching_versions: matching_versions
+                        .iter()
+                        .map(|version| version.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
                 })
             }
-            _ => unreachable!(),
         };
         let installation_dir = PathBuf::from(&config.versions_dir()).join(version.to_string());
         if !installation_dir.exists() {
@@ -52,21 +77,13 @@ impl crate::command::Command for Uninstall {
                 version: current_version,
             });
         }
-        outln!(config#Info, "{} Uninstalling {}", "==>".green(), format!("Ruby {}", current_version).green());
-        if symlink_exists(
-            config
-                .frum_path
-                .clone()
-                .ok_or(FrumError::FrumPathNotFound)?,
-            &version,
-        )? {
-            debug!("remove frum path symlink");
-            remove_symlink_dir(
-                &config
-                    .frum_path
-                    .clone()
-                    .ok_or(FrumError::FrumPathNotFound)?,
-            )?;
+        outln!(config#Info, "{} Uninstalling {}", "==>".green(), format!("Ruby {}", version).green());
+        // With shims there is no symlink of the shell to remove.
+        if let Some(frum_path) = &config.frum_path {
+            if symlink_exists(frum_path.clone(), &version)? {
+                debug!("remove frum path symlink");
+                remove_symlink_dir(frum_path)?;
+            }
         }
         if symlink_exists(config.default_version_dir(), &version)? {
             debug!("remove default alias symlink");
@@ -74,6 +91,9 @@ impl crate::command::Command for Uninstall {
         }
         debug!("remove dir");
         std::fs::remove_dir_all(&installation_dir)?;
+        if config.shims_dir().exists() {
+            rehash(config)?;
+        }
         Ok(())
     }
 }
@@ -83,3 +103,43 @@ fn symlink_exists(to: PathBuf, version: &Version) -> Result<bool, FrumError> {
     Ok(std::fs::read_link(to)?.components().last()
         == Some(Component::Normal(OsStr::new(&version.to_string()))))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use std::str::FromStr;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_uninstall_requires_one_matching_version() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        for version in &["3.2.1", "3.2.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+
+        for version in &["3", "3.2", "latest", ">= 2.0"] {
+            let result = Uninstall {
+                version: InputVersion::from_str(version).unwrap(),
+            }
+            .apply(&config);
+            assert!(matches!(
+                result,
+                Err(FrumError::AmbiguousVersion { matching_versions, .. })
+                    if matching_versions == "3.2.1, 3.2.2"
+            ));
+        }
+        assert!(config.versions_dir().join("3.2.1").exists());
+        assert!(config.versions_dir().join("3.2.2").exists());
+        assert!(matches!(
+            Uninstall {
+                version: InputVersion::from_str("3.3").unwrap(),
+            }
+            .apply(&config),
+            Err(FrumError::VersionNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::commands::exec::{resolve_version, FrumError as ExecError};
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{installed_versions, Version};
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ExecError(#[from] ExecError),
+    #[error("Can't find {name} on PATH outside of the shims directory")]
+    SystemCommandNotFound { name: String },
+    #[error("{name} isn't installed for Ruby {version}")]
+    CommandNotFound { name: String, version: Version },
+    #[error("{name} isn't installed for Ruby {version}. It's available in: {available}")]
+    CommandInOtherVersions {
+        name: String,
+        version: Version,
+        available: String,
+    },
+}
+
+pub struct Which {
+    pub command: String,
+    pub version: Option<InputVersion>,
+}
+
+impl crate::command::Command for Which {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let version = resolve_version(config, self.version.clone())?;
+        let path = locate(config, &version, &self.command)?;
+        outln!(config#Info, "{}", path.display());
+        Ok(())
+    }
+}
+
+/// The directories with the executables of `version`: its `bin` directory followed by
+/// the `bin` directories of its gem homes.
+pub fn bin_dirs(config: &FrumConfig, version: &Version) -> Vec<PathBuf> {
+    let mut dirs = vec![config.version_bin_dir(version)];
+    let gems_dir = config
+        .versions_dir()
+        .join(version.to_string())
+        .join("lib")
+        .join("ruby")
+        .join("gems");
+    if let Ok(entries) = gems_dir.read_dir() {
+        let mut gem_bin_dirs = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("bin"))
+            .filter(|dir| dir.is_dir())
+            .collect::<Vec<_>>();
+        gem_bin_dirs.sort();
+        dirs.extend(gem_bin_dirs);
+    }
+    dirs
+}
+
+/// Finds `name` in `version`, or on `PATH` without the shims for the system version.
+pub fn find_executable(config: &FrumConfig, version: &Version, name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    let dirs = match version {
+        Version::System => system_path(config),
+        _ => bin_dirs(config, version),
+    };
+    dirs.into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// The directories of `PATH` without the shims and the version of the current shell.
+pub fn system_path(config: &FrumConfig) -> Vec<PathBuf> {
+    let shims_dir = config.shims_dir();
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path)
+        .filter(|dir| *dir != shims_dir)
+        .filter(|dir| match &config.frum_path {
+            Some(frum_path) => !dir.starts_with(frum_path),
+            None => true,
+        })
+        .collect()
+}
+
+/// Like `find_executable`, but the error names the versions that have `name`.
+pub fn locate(config: &FrumConfig, version: &Version, name: &str) -> Result<PathBuf, FrumError> {
+    if let Some(path) = find_executable(config, version, name) {
+        return Ok(path);
+    }
+    let name = name.to_string();
+    let version = version.clone();
+    if version == Version::System {
+        return Err(FrumError::SystemCommandNotFound { name });
+    }
+    let available = installed_versions(config)?
+        .into_iter()
+        .filter(|version| find_executable(config, version, &name).is_some())
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+    Err(if available.is_empty() {
+        FrumError::CommandNotFound { name, version }
+    } else {
+        FrumError::CommandInOtherVersions {
+            name,
+            version,
+            available: available.join(", "),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_which() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..FrumConfig::default()
+        };
+        let version_dir = config.versions_dir().join("3.1.4");
+        let gem_bin_dir = version_dir.join("lib/ruby/gems/3.1.0/bin");
+        std::fs::create_dir_all(version_dir.join("bin")).unwrap();
+        std::fs::create_dir_all(&gem_bin_dir).unwrap();
+        std::fs::create_dir_all(config.versions_dir().join("3.2.2/bin")).unwrap();
+        std::fs::write(version_dir.join("bin/ruby"), "").unwrap();
+        std::fs::write(gem_bin_dir.join("rails"), "").unwrap();
+
+        let version = Version::parse("3.1.4").unwrap();
+        assert_eq!(
+            find_executable(&config, &version, "ruby"),
+            Some(version_dir.join("bin/ruby"))
+        );
+        assert_eq!(
+            find_executable(&config, &version, "rails"),
+            Some(gem_bin_dir.join("rails"))
+        );
+
+        let result = Which {
+            command: "rails".to_string(),
+            version: Some(InputVersion::Full(Version::parse("3.2.2").unwrap())),
+        }
+        .apply(&config);
+        assert!(matches!(
+            result,
+            Err(FrumError::CommandInOtherVersions { available, .. }) if available == "3.1.4"
+        ));
+        let result = Which {
+            command: "rake".to_string(),
+            version: Some(InputVersion::Full(Version::parse("3.2.2").unwrap())),
+        }
+        .apply(&config);
+        assert!(matches!(result, Err(FrumError::CommandNotFound { .. })));
+    }
+}
@@ -1,5 +1,12 @@
+use crate::input_version::InputVersion;
 use crate::log::LogLevel;
+use crate::version::Version;
+use crate::version_file::{parse_version_files, VersionFile};
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a cached index is used before revalidating it with the mirror.
+const DEFAULT_INDEX_TTL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug)]
 pub struct FrumConfig {
@@ -7,6 +14,12 @@ pub struct FrumConfig {
     pub ruby_build_mirror: reqwest::Url,
     pub log_level: LogLevel,
     pub frum_path: Option<PathBuf>,
+    pub offline: bool,
+    pub index_ttl: Duration,
+    /// The version files to look for, in order of priority.
+    pub version_files: Vec<VersionFile>,
+    /// The version requested by `$FRUM_VERSION`, which overrides version files.
+    pub env_version: Option<InputVersion>,
 }
 
 impl Default for FrumConfig {
@@ -18,10 +31,34 @@ impl Default for FrumConfig {
             frum_path: std::env::var("FRUM_MULTISHELL_PATH")
                 .map(std::path::PathBuf::from)
                 .ok(),
+            offline: false,
+            index_ttl: std::env::var("FRUM_INDEX_TTL")
+                .ok()
+                .and_then(|ttl| ttl.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_INDEX_TTL),
+            version_files: std::env::var("FRUM_VERSION_FILES")
+                .ok()
+                .and_then(|names| parse_version_files(&names).ok())
+                .unwrap_or_else(|| VersionFile::DEFAULT.to_vec()),
+            env_version: env_version().unwrap_or_else(|err| {
+                log::warn!("Ignoring $FRUM_VERSION: {}", err);
+                None
+            }),
         }
     }
 }
 
+/// Parses `$FRUM_VERSION`. `main` reports an invalid value instead of silently
+/// falling back to the version files.
+pub fn env_version() -> Result<Option<InputVersion>, crate::version::ParseError> {
+    std::env::var("FRUM_VERSION")
+        .ok()
+        .filter(|version| !version.trim().is_empty())
+        .map(|version| version.parse())
+        .transpose()
+}
+
 impl FrumConfig {
     pub fn base_dir(&self) -> std::path::PathBuf {
         ensure_dir_exists((self.base_dir.clone()).unwrap_or_else(|| {
@@ -42,9 +79,56 @@ impl FrumConfig {
     pub fn aliases_dir(&self) -> std::path::PathBuf {
         ensure_dir_exists(self.base_dir().join("aliases"))
     }
+
+    pub fn logs_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(self.base_dir().join("logs"))
+    }
+
+    pub fn locks_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(self.base_dir().join("locks"))
+    }
+
+    pub fn cache_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(self.base_dir().join("cache"))
+    }
+
+    /// Only exists once shims are enabled with `frum rehash` or `frum init --shims`.
+    pub fn shims_dir(&self) -> std::path::PathBuf {
+        self.base_dir().join("shims")
+    }
+
+    /// The directory with the executables of an installed version.
+    pub fn version_bin_dir(&self, version: &Version) -> std::path::PathBuf {
+        let version_dir = self.versions_dir().join(version.to_string());
+        if cfg!(windows) {
+            version_dir
+        } else {
+            version_dir.join("bin")
+        }
+    }
 }
 
 fn ensure_dir_exists<T: AsRef<std::path::Path>>(path: T) -> T {
     std::fs::create_dir_all(path.as_ref()).ok();
     path
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::FrumConfig;
+    use tempfile::tempdir;
+
+    /// A config with a multishell path and empty installations of `versions`.
+    pub fn config_with_versions(versions: &[&str]) -> FrumConfig {
+        let base_dir = tempdir().unwrap().path().to_path_buf();
+        let config = FrumConfig {
+            frum_path: Some(base_dir.join("multishell")),
+            base_dir: Some(base_dir),
+            ..FrumConfig::default()
+        };
+        for version in versions {
+            std::fs::create_dir_all(config.versions_dir().join(version).join("bin")).unwrap();
+        }
+        config
+    }
+}
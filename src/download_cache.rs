@@ -0,0 +1,223 @@
+use crate::checksum::{is_sha256, Sha256Reader};
+use crate::config::FrumConfig;
+use log::debug;
+use std::fs::File;
+use std::path::PathBuf;
+
+/// An archive stored in the download cache at `<cache_dir>/archives/<sha256>/<file_name>`.
+#[derive(Debug)]
+pub struct CachedArchive {
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+impl CachedArchive {
+    pub fn file_name(&self) -> &str {
+        self.path
+            .file_name()
+            .expect("must have basename")
+            .to_str()
+            .unwrap()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.path.metadata().map(|x| x.len()).unwrap_or(0)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match File::open(&self.path).and_then(|file| Sha256Reader::new(file).finish()) {
+            Ok(actual) => actual == self.sha256,
+            Err(_) => false,
+        }
+    }
+
+    /// Removes the archive, and its digest directory once that is empty.
+    pub fn remove(&self, config: &FrumConfig) -> std::io::Result<()> {
+        std::fs::remove_file(&self.path)?;
+        match self.path.parent() {
+            Some(dir) if dir.parent() == Some(archives_dir(config).as_path()) => {
+                if dir.read_dir()?.next().is_none() {
+                    std::fs::remove_dir(dir)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn archives_dir(config: &FrumConfig) -> PathBuf {
+    config.cache_dir().join("archives")
+}
+
+/// Returns every archive in the cache, including ones whose contents are corrupted.
+pub fn list(config: &FrumConfig) -> std::io::Result<Vec<CachedArchive>> {
+    let archives_dir = archives_dir(config);
+    if !archives_dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut archives = vec![];
+    for entry in archives_dir.read_dir()? {
+        let entry = entry?;
+        if crate::version::is_dotfile(&entry) || !entry.path().is_dir() {
+            continue;
+        }
+        let sha256 = entry.file_name().to_string_lossy().to_string();
+        for file in entry.path().read_dir()? {
+            archives.push(CachedArchive {
+                path: file?.path(),
+                sha256: sha256.clone(),
+            })
+        }
+    }
+    archives.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    Ok(archives)
+}
+
+/// Looks up a valid cached copy of `file_name`. Entries whose contents don't
+/// match their checksum are removed on the way.
+pub fn find(config: &FrumConfig, file_name: &str, sha256: Option<&str>) -> Option<CachedArchive> {
+    let candidates = match sha256 {
+        Some(sha256) if !is_sha256(sha256) => {
+            debug!("Ignoring invalid sha256 {:?}", sha256);
+            return None;
+        }
+        Some(sha256) => vec![CachedArchive {
+            path: archives_dir(config).join(sha256).join(file_name),
+            sha256: sha256.to_string(),
+        }],
+        None => list(config)
+            .ok()?
+            .into_iter()
+            .filter(|x| x.file_name() == file_name)
+            .collect(),
+    };
+    for archive in candidates {
+        if !archive.path.exists() {
+            continue;
+        }
+        if archive.is_valid() {
+            return Some(archive);
+        }
+        debug!("Removing corrupted cache entry {}", archive.path.display());
+        archive.remove(config).ok();
+    }
+    None
+}
+
+/// Creates a temporary file next to the cached archives so that it can be
+/// moved into place atomically once the download completes.
+pub fn temp_file(config: &FrumConfig) -> std::io::Result<tempfile::NamedTempFile> {
+    let archives_dir = archives_dir(config);
+    std::fs::create_dir_all(&archives_dir)?;
+    tempfile::Builder::new()
+        .prefix(".download")
+        .tempfile_in(archives_dir)
+}
+
+pub fn store(
+    config: &FrumConfig,
+    file: tempfile::NamedTempFile,
+    file_name: &str,
+    sha256: &str,
+) -> std::io::Result<CachedArchive> {
+    if !is_sha256(sha256) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid sha256: {:?}", sha256),
+        ));
+    }
+    let dir = archives_dir(config).join(sha256);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    file.persist(&path).map_err(|err| err.error)?;
+    Ok(CachedArchive {
+        path,
+        sha256: sha256.to_string(),
+    })
+}
+
+/// Returns leftovers of interrupted downloads.
+pub fn partial_downloads(config: &FrumConfig) -> std::io::Result<Vec<PathBuf>> {
+    let archives_dir = archives_dir(config);
+    if !archives_dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut paths = vec![];
+    for entry in archives_dir.read_dir()? {
+        let entry = entry?;
+        if crate::version::is_dotfile(&entry) {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
+
+pub fn clean(config: &FrumConfig) -> std::io::Result<()> {
+    let archives_dir = archives_dir(config);
+    if archives_dir.exists() {
+        std::fs::remove_dir_all(archives_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn store_bytes(config: &FrumConfig, file_name: &str, contents: &[u8]) -> CachedArchive {
+        let mut file = temp_file(config).unwrap();
+        file.write_all(contents).unwrap();
+        let sha256 = Sha256Reader::new(contents).finish().unwrap();
+        store(config, file, file_name, &sha256).unwrap()
+    }
+
+    #[test]
+    fn test_find_cached_archive() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let archive = store_bytes(&config, "ruby-2.7.0.tar.xz", b"ruby");
+
+        assert!(find(&config, "ruby-2.7.0.tar.xz", Some(&archive.sha256)).is_some());
+        assert!(find(&config, "ruby-2.7.0.tar.xz", None).is_some());
+        assert!(find(&config, "ruby-2.7.0.tar.xz", Some(&"0".repeat(64))).is_none());
+        assert!(find(&config, "ruby-2.6.4.tar.xz", None).is_none());
+    }
+
+    #[test]
+    fn test_find_ignores_invalid_sha256() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let outside = base_dir.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("ruby-2.7.0.tar.xz"), b"ruby").unwrap();
+
+        for sha256 in &[outside.to_str().unwrap(), "..", "../../outside"] {
+            assert!(find(&config, "ruby-2.7.0.tar.xz", Some(sha256)).is_none());
+        }
+        assert!(outside.join("ruby-2.7.0.tar.xz").exists());
+        let file = temp_file(&config).unwrap();
+        assert!(store(&config, file, "ruby-2.7.0.tar.xz", "..").is_err());
+    }
+
+    #[test]
+    fn test_find_removes_corrupted_archive() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let archive = store_bytes(&config, "ruby-2.7.0.tar.xz", b"ruby");
+        std::fs::write(&archive.path, b"broken").unwrap();
+
+        assert!(find(&config, "ruby-2.7.0.tar.xz", None).is_none());
+        assert!(!archive.path.exists());
+        assert!(list(&config).unwrap().is_empty());
+    }
+}
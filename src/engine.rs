@@ -0,0 +1,133 @@
+use crate::version::EngineVersion;
+use reqwest::Url;
+use std::str::FromStr;
+
+/// A Ruby implementation other than CRuby. Their versions are written with the
+/// engine name as a prefix, e.g. `jruby-9.4.5.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Engine {
+    Jruby,
+    Truffleruby,
+    Mruby,
+}
+
+impl Engine {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Jruby => "jruby",
+            Self::Truffleruby => "truffleruby",
+            Self::Mruby => "mruby",
+        }
+    }
+
+    /// JRuby and TruffleRuby are distributed as prebuilt archives that only need
+    /// to be unpacked. mruby is built from source with rake.
+    pub fn is_prebuilt(self) -> bool {
+        matches!(self, Self::Jruby | Self::Truffleruby)
+    }
+
+    /// Returns where to download `version` from and the file name to cache it as,
+    /// or `None` if the engine isn't available for this platform.
+    pub fn package(self, version: &EngineVersion) -> Option<(Url, String)> {
+        let numbers = version.number();
+        let (url, file_name) = match self {
+            Self::Jruby => {
+                let file_name = format!(
+                    "jruby-dist-{}-bin.{}",
+                    numbers,
+                    if cfg!(windows) { "zip" } else { "tar.gz" }
+                );
+                (
+                    format!(
+                        "https://repo1.maven.org/maven2/org/jruby/jruby-dist/{}/{}",
+                        numbers, file_name
+                    ),
+                    file_name,
+                )
+            }
+            Self::Truffleruby => {
+                let file_name =
+                    format!("truffleruby-{}-{}.tar.gz", numbers, truffleruby_platform()?);
+                (
+                    format!(
+                        "https://github.com/oracle/truffleruby/releases/download/graal-{}/{}",
+                        numbers, file_name
+                    ),
+                    file_name,
+                )
+            }
+            Self::Mruby => (
+                format!("https://github.com/mruby/mruby/archive/{}.tar.gz", numbers),
+                format!("mruby-{}.tar.gz", numbers),
+            ),
+        };
+        Some((Url::parse(&url).expect("invalid engine url"), file_name))
+    }
+}
+
+fn truffleruby_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux-amd64"),
+        ("linux", "aarch64") => Some("linux-aarch64"),
+        ("macos", "x86_64") => Some("macos-amd64"),
+        ("macos", "aarch64") => Some("macos-aarch64"),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Engine {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jruby" => Ok(Self::Jruby),
+            "truffleruby" => Ok(Self::Truffleruby),
+            "mruby" => Ok(Self::Mruby),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::version::Version;
+
+    fn package(version: &str) -> Option<(String, String)> {
+        match Version::parse(version).unwrap() {
+            Version::Engine(version) => version
+                .engine
+                .package(&version)
+                .map(|(url, file_name)| (url.to_string(), file_name)),
+            _ => panic!("not an engine version"),
+        }
+    }
+
+    #[test]
+    fn test_engine_packages() {
+        let (url, file_name) = package("jruby-9.4.5.0").unwrap();
+        assert!(url.starts_with("https://repo1.maven.org/maven2/org/jruby/jruby-dist/9.4.5.0/"));
+        assert!(url.ends_with(&file_name));
+        assert!(file_name.starts_with("jruby-dist-9.4.5.0-bin."));
+
+        assert_eq!(
+            package("mruby-3.2.0").unwrap(),
+            (
+                "https://github.com/mruby/mruby/archive/3.2.0.tar.gz".to_string(),
+                "mruby-3.2.0.tar.gz".to_string()
+            )
+        );
+
+        if let Some((url, file_name)) = package("truffleruby-23.1.1") {
+            assert!(url.starts_with(
+                "https://github.com/oracle/truffleruby/releases/download/graal-23.1.1/"
+            ));
+            assert!(file_name.starts_with("truffleruby-23.1.1-"));
+        }
+    }
+}
@@ -1,32 +1,88 @@
-use crate::version::Version;
+use crate::config::FrumConfig;
+use crate::engine::Engine;
+use crate::requirement::Requirement;
+use crate::version::{installed_versions, Version};
 use std::str::FromStr;
 
 #[derive(Clone, Debug)]
 pub enum InputVersion {
     Major(u64),
     MajorMinor(u64, u64),
+    /// An engine name with an incomplete version, like `jruby` or `jruby-9.4`.
+    Engine(Engine, Vec<u64>),
+    /// A Bundler style requirement like `~> 3.2.0`.
+    Requirement(Requirement),
+    /// The newest CRuby release.
+    Latest,
+    /// The newest release of the CRuby series before the latest one, which has had
+    /// a year of fixes.
+    Lts,
     Full(Version),
 }
 
 impl InputVersion {
-    pub fn to_version<'a, T>(&self, available_versions: T) -> Option<&'a Version>
+    /// Picks the latest matching version. A partial version only resolves to a
+    /// prerelease if `include_prerelease` is set.
+    pub fn to_version<'a, T>(
+        &self,
+        available_versions: T,
+        include_prerelease: bool,
+    ) -> Option<&'a Version>
     where
         T: IntoIterator<Item = &'a Version>,
     {
-        available_versions
+        let candidates = available_versions
             .into_iter()
             .filter(|x| self.matches(x))
-            .max()
+            .filter(|x| include_prerelease || matches!(self, Self::Full(_)) || !x.is_prerelease());
+        match self {
+            Self::Lts => {
+                let candidates = candidates.collect::<Vec<_>>();
+                let series = |version: &Version| match version {
+                    Version::Ruby(version) => Some((version.major, version.minor)),
+                    _ => None,
+                };
+                let latest_series = candidates.iter().filter_map(|x| series(x)).max();
+                candidates
+                    .iter()
+                    .filter(|x| series(x) < latest_series)
+                    .max()
+                    .or_else(|| candidates.iter().max())
+                    .copied()
+            }
+            _ => candidates.max(),
+        }
+    }
+
+    /// Picks the installed version this refers to. Installed prereleases are
+    /// considered when no release matches, since installing one was deliberate.
+    pub fn to_installed_version(&self, config: &FrumConfig) -> std::io::Result<Option<Version>> {
+        if let Self::Full(version) = self {
+            return Ok(Some(version.clone()));
+        }
+        let installed_versions = installed_versions(config)?;
+        Ok(self
+            .to_version(&installed_versions, false)
+            .or_else(|| self.to_version(&installed_versions, true))
+            .cloned())
     }
 
     pub fn matches(&self, version: &Version) -> bool {
         match (self, version) {
             (Self::Full(a), b) => a == b,
             (_, Version::System) => false,
-            (Self::Major(major), Version::Semver(other)) => *major == other.major,
-            (Self::MajorMinor(major, minor), Version::Semver(other)) => {
+            (Self::Requirement(requirement), version) => requirement.matches(version),
+            (Self::Latest, version) | (Self::Lts, version) => matches!(version, Version::Ruby(_)),
+            (Self::Major(major), Version::Ruby(other)) => *major == other.major,
+            (Self::MajorMinor(major, minor), Version::Ruby(other)) => {
                 *major == other.major && *minor == other.minor
             }
+            (Self::Engine(engine, numbers), Version::Engine(other)) => {
+                *engine == other.engine && other.numbers.starts_with(numbers)
+            }
+            (Self::Engine(..), Version::Ruby(_))
+            | (Self::Major(_), Version::Engine(_))
+            | (Self::MajorMinor(..), Version::Engine(_)) => false,
         }
     }
 }
@@ -35,18 +91,42 @@ impl std::fmt::Display for InputVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Full(x) => x.fmt(f),
+            Self::Requirement(requirement) => requirement.fmt(f),
+            Self::Latest => write!(f, "latest"),
+            Self::Lts => write!(f, "lts"),
             Self::Major(major) => write!(f, "{}.x.x", major),
             Self::MajorMinor(major, minor) => write!(f, "{}.{}.x", major, minor),
+            Self::Engine(engine, numbers) if numbers.is_empty() => write!(f, "{}", engine),
+            Self::Engine(engine, numbers) => {
+                write!(f, "{}-", engine)?;
+                for number in numbers {
+                    write!(f, "{}.", number)?;
+                }
+                write!(f, "x")
+            }
         }
     }
 }
 
 impl FromStr for InputVersion {
-    type Err = semver::SemVerError;
+    type Err = crate::version::ParseError;
     fn from_str(s: &str) -> Result<InputVersion, Self::Err> {
+        if s.trim_start().starts_with(|c| "~<>=!".contains(c)) {
+            return Requirement::from_str(s)
+                .map(Self::Requirement)
+                .map_err(|_| crate::version::ParseError::new(s));
+        }
+        match s.trim().to_lowercase().as_str() {
+            "latest" => return Ok(Self::Latest),
+            "lts" => return Ok(Self::Lts),
+            _ => {}
+        }
         match Version::parse(s) {
             Ok(v) => Ok(Self::Full(v)),
             Err(e) => {
+                if let Some(version) = parse_partial_engine_version(s.trim()) {
+                    return Ok(version);
+                }
                 let mut parts = s.trim().split('.');
                 match (next_of::<u64, _>(&mut parts), next_of::<u64, _>(&mut parts)) {
                     (Some(major), None) => Ok(Self::Major(major)),
@@ -58,7 +138,155 @@ impl FromStr for InputVersion {
     }
 }
 
+fn parse_partial_engine_version(s: &str) -> Option<InputVersion> {
+    let lowercased = s.to_lowercase();
+    let (engine, numbers) = match lowercased.split_once('-') {
+        Some((engine, numbers)) => (engine, Some(numbers)),
+        None => (lowercased.as_str(), None),
+    };
+    let engine = engine.parse::<Engine>().ok()?;
+    let numbers = match numbers {
+        Some(numbers) => numbers
+            .split('.')
+            .map(|number| number.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?,
+        None => vec![],
+    };
+    Some(InputVersion::Engine(engine, numbers))
+}
+
 fn next_of<'a, T: FromStr, It: Iterator<Item = &'a str>>(i: &mut It) -> Option<T> {
     let x = i.next()?;
     T::from_str(x).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_version_skips_prereleases() {
+        let versions = [
+            "3.2.2",
+            "3.2.2-patched",
+            "3.3.0",
+            "3.4.0-preview1",
+            "3.4.0-preview2",
+            "1.9.3-p551",
+        ]
+        .iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect::<Vec<_>>();
+        let resolve = |input: &str, include_prerelease| {
+            InputVersion::from_str(input)
+                .unwrap()
+                .to_version(&versions, include_prerelease)
+                .map(|version| version.to_string())
+        };
+
+        assert_eq!(resolve("3", false), Some("3.3.0".to_string()));
+        assert_eq!(resolve("3.4", false), None);
+        assert_eq!(resolve("3.4", true), Some("3.4.0-preview2".to_string()));
+        assert_eq!(
+            resolve("3.4.0-preview1", false),
+            Some("3.4.0-preview1".to_string())
+        );
+        assert_eq!(resolve("1.9", false), Some("1.9.3-p551".to_string()));
+        assert_eq!(resolve("3.2", false), Some("3.2.2-patched".to_string()));
+    }
+
+    #[test]
+    fn test_partial_engine_version() {
+        let versions = [
+            "3.3.0",
+            "jruby-9.3.13.0",
+            "jruby-9.4.5.0",
+            "jruby-9.4.10.0",
+            "truffleruby-23.1.1",
+        ]
+        .iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect::<Vec<_>>();
+        let resolve = |input: &str| {
+            InputVersion::from_str(input)
+                .unwrap()
+                .to_version(&versions, false)
+                .map(|version| version.to_string())
+        };
+
+        assert_eq!(resolve("jruby"), Some("jruby-9.4.10.0".to_string()));
+        assert_eq!(resolve("jruby-9.3"), Some("jruby-9.3.13.0".to_string()));
+        assert_eq!(
+            resolve("truffleruby-23"),
+            Some("truffleruby-23.1.1".to_string())
+        );
+        assert_eq!(resolve("mruby"), None);
+        assert_eq!(resolve("9"), None);
+        assert_eq!(
+            InputVersion::from_str("jruby-9.4").unwrap().to_string(),
+            "jruby-9.4.x"
+        );
+        assert!(InputVersion::from_str("rbx-5").is_err());
+    }
+
+    #[test]
+    fn test_requirement() {
+        let versions = ["3.1.4", "3.2.0", "3.2.2", "3.3.0"]
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        let resolve = |input: &str| {
+            InputVersion::from_str(input)
+                .unwrap()
+                .to_version(&versions, false)
+                .map(|version| version.to_string())
+        };
+        assert_eq!(resolve("~> 3.2.0"), Some("3.2.2".to_string()));
+        assert_eq!(resolve(">= 3.0, < 3.2"), Some("3.1.4".to_string()));
+        assert_eq!(resolve("~> 2.7"), None);
+        assert!(InputVersion::from_str("~> latest").is_err());
+        assert_eq!(resolve(">=3.1 <3.3"), Some("3.2.2".to_string()));
+        assert_eq!(resolve("~>3.2.0"), Some("3.2.2".to_string()));
+        assert_eq!(resolve("3.x"), Some("3.3.0".to_string()));
+        assert_eq!(resolve("3.2.x"), Some("3.2.2".to_string()));
+    }
+
+    #[test]
+    fn test_keywords() {
+        let versions = ["3.2.0", "3.2.2", "3.3.0", "3.4.0-preview1", "jruby-9.4.5.0"]
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        let resolve = |input: &str, versions: &[Version]| {
+            InputVersion::from_str(input)
+                .unwrap()
+                .to_version(versions, false)
+                .map(|version| version.to_string())
+        };
+        assert_eq!(resolve("latest", &versions), Some("3.3.0".to_string()));
+        assert_eq!(resolve("LTS", &versions), Some("3.2.2".to_string()));
+        assert_eq!(resolve("lts", &versions[..2]), Some("3.2.2".to_string()));
+        assert_eq!(resolve("latest", &versions[4..]), None);
+    }
+
+    #[test]
+    fn test_to_installed_version() {
+        let config = FrumConfig {
+            base_dir: Some(tempfile::tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        for version in &["3.2.2", "3.4.0-preview1"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let resolve = |input: &str| {
+            InputVersion::from_str(input)
+                .unwrap()
+                .to_installed_version(&config)
+                .unwrap()
+                .map(|version| version.to_string())
+        };
+        assert_eq!(resolve("3"), Some("3.2.2".to_string()));
+        assert_eq!(resolve("3.4"), Some("3.4.0-preview1".to_string()));
+        assert_eq!(resolve("~> 2.7"), None);
+    }
+}
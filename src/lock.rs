@@ -0,0 +1,162 @@
+use crate::config::FrumConfig;
+use crate::outln;
+use colored::Colorize;
+use fs2::FileExt;
+use log::debug;
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Building Ruby can take a long time, so waiting for another installation
+/// of the same version is allowed to take a while.
+const INSTALLATION_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const ALIASES_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Timed out after {}s waiting for another frum process holding {path:?}", timeout.as_secs())]
+    Timeout { path: PathBuf, timeout: Duration },
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::IoError(err) => err,
+            err @ Error::Timeout { .. } => std::io::Error::new(std::io::ErrorKind::TimedOut, err),
+        }
+    }
+}
+
+/// An exclusive lock on a file in `<frum_dir>/locks`, released on drop.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn open(config: &FrumConfig, name: &str) -> std::io::Result<(File, PathBuf)> {
+        let path = config.locks_dir().join(format!("{}.lock", name));
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)?;
+        Ok((file, path))
+    }
+
+    pub fn try_acquire(config: &FrumConfig, name: &str) -> std::io::Result<Option<Self>> {
+        let (file, path) = Self::open(config, name)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(Self { file, path })),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Waits until the lock is free, printing `waiting_message` once if it
+    /// is held by another process.
+    pub fn acquire(
+        config: &FrumConfig,
+        name: &str,
+        waiting_message: &str,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        let started = Instant::now();
+        let mut waiting = false;
+        loop {
+            if let Some(lock) = Self::try_acquire(config, name)? {
+                debug!("acquired {}", lock.path.display());
+                return Ok(lock);
+            }
+            if !waiting {
+                outln!(config#Info, "{} {}", "==>".yellow(), waiting_message);
+                waiting = true;
+            }
+            if started.elapsed() >= timeout {
+                return Err(Error::Timeout {
+                    path: config.locks_dir().join(format!("{}.lock", name)),
+                    timeout,
+                });
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        debug!("release {}", self.path.display());
+        self.file.unlock().ok();
+    }
+}
+
+fn installation_lock_name(version_name: &str) -> String {
+    format!("install-{}", version_name)
+}
+
+/// Locks the installation directory of a version.
+pub fn lock_installation(config: &FrumConfig, version_name: &str) -> Result<FileLock, Error> {
+    FileLock::acquire(
+        config,
+        &installation_lock_name(version_name),
+        &format!(
+            "Waiting for another frum process to finish installing {}...",
+            version_name
+        ),
+        INSTALLATION_TIMEOUT,
+    )
+}
+
+pub fn try_lock_installation(
+    config: &FrumConfig,
+    version_name: &str,
+) -> std::io::Result<Option<FileLock>> {
+    FileLock::try_acquire(config, &installation_lock_name(version_name))
+}
+
+/// Locks the aliases directory.
+pub fn lock_aliases(config: &FrumConfig) -> Result<FileLock, Error> {
+    FileLock::acquire(
+        config,
+        "aliases",
+        "Waiting for another frum process to update aliases...",
+        ALIASES_TIMEOUT,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let lock = try_lock_installation(&config, "2.7.0").unwrap();
+        assert!(lock.is_some());
+        assert!(try_lock_installation(&config, "2.7.0").unwrap().is_none());
+        assert!(try_lock_installation(&config, "2.6.4").unwrap().is_some());
+
+        drop(lock);
+        assert!(try_lock_installation(&config, "2.7.0").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_acquire_timeout() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        };
+        let _lock = FileLock::try_acquire(&config, "aliases").unwrap();
+        let result = FileLock::acquire(&config, "aliases", "waiting", Duration::from_millis(1));
+        assert!(matches!(result, Err(Error::Timeout { .. })));
+    }
+}
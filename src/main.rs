@@ -1,11 +1,18 @@
 mod alias;
 mod archive;
+mod build_log;
+mod checksum;
 mod cli;
 mod command;
 mod commands;
 mod config;
+mod download_cache;
+mod engine;
 mod input_version;
+mod lock;
+mod progress;
 mod remote_ruby_index;
+mod requirement;
 mod shell;
 mod symlink;
 mod version;
@@ -14,14 +21,23 @@ mod version_file;
 #[macro_use]
 mod log;
 
+use colored::Colorize;
 use command::Command;
 use std::str::FromStr;
 
 fn main() {
     env_logger::init();
+    let mut config = config::FrumConfig::default();
+    if let Err(err) = config::env_version() {
+        outln!(config#Error, "{} {}", "error: invalid $FRUM_VERSION:".red().bold(), format!("{}", err).red());
+        std::process::exit(1);
+    }
+    if let Some(shim) = commands::shim::Shim::from_args(&config, std::env::args_os()) {
+        shim.call(&config);
+        return;
+    }
     let matches = cli::build_cli().get_matches();
 
-    let mut config = config::FrumConfig::default();
     if let Some(log_level) = matches.value_of("log-level") {
         config.log_level = log::LogLevel::from_str(log_level).expect("invalid log level")
     }
@@ -32,9 +48,53 @@ fn main() {
     if let Some(base_dir) = matches.value_of("base-dir") {
         config.base_dir = Some(std::path::PathBuf::from(base_dir))
     };
+    if matches.is_present("offline") {
+        config.offline = true;
+    }
+    if let Some(index_ttl) = matches.value_of("index-ttl") {
+        config.index_ttl =
+            std::time::Duration::from_secs(index_ttl.parse().expect("invalid index ttl"))
+    };
+    if let Some(version_files) = matches.value_of("version-files") {
+        config.version_files =
+            version_file::parse_version_files(version_files).expect("invalid version files")
+    };
     match matches.subcommand() {
-        ("init", _) => commands::init::Init {}.call(&config),
+        ("init", Some(sub_matches)) => commands::init::Init {
+            shell: sub_matches
+                .value_of("shell")
+                .map(|shell| shell::shell_from_name(shell).expect("invalid shell")),
+            shims: sub_matches.is_present("shims"),
+            hook: match sub_matches.value_of("hook") {
+                Some("cd") => commands::init::Hook::Cd,
+                _ => commands::init::Hook::Prompt,
+            },
+        }
+        .call(&config),
+        ("rehash", _) => commands::rehash::Rehash {}.call(&config),
         ("versions", _) => commands::versions::Versions {}.call(&config),
+        ("exec", Some(sub_matches)) => commands::exec::Exec {
+            version: sub_matches.value_of("version").map(|version| {
+                input_version::InputVersion::from_str(version).expect("invalid version")
+            }),
+            command: sub_matches
+                .values_of("command")
+                .unwrap()
+                .map(|arg| arg.to_string())
+                .collect(),
+        }
+        .call(&config),
+        ("which", Some(sub_matches)) => commands::which::Which {
+            command: sub_matches.value_of("command").unwrap().to_string(),
+            version: sub_matches.value_of("version").map(|version| {
+                input_version::InputVersion::from_str(version).expect("invalid version")
+            }),
+        }
+        .call(&config),
+        ("current", Some(sub_matches)) => commands::current::Current {
+            json: sub_matches.is_present("json"),
+        }
+        .call(&config),
         ("global", Some(sub_matches)) => commands::global::Global {
             version: input_version::InputVersion::from_str(
                 sub_matches.value_of("version").unwrap(),
@@ -46,21 +106,51 @@ fn main() {
             version: sub_matches.value_of("version").map(|version| {
                 input_version::InputVersion::from_str(version).expect("invalid version")
             }),
+            unset: sub_matches.is_present("unset"),
+            no_write: sub_matches.is_present("no-write"),
+            if_changed: sub_matches.is_present("if-changed"),
         }
         .call(&config),
         ("install", Some(sub_matches)) => {
             if sub_matches.is_present("list") {
-                commands::install_list::InstallList {}.call(&config);
+                commands::install_list::InstallList {
+                    include_prerelease: sub_matches.is_present("include-prerelease"),
+                }
+                .call(&config);
                 return;
             }
-            commands::install::Install {
-                version: sub_matches.value_of("version").map(|version| {
+            let local_source = if let Some(path) = sub_matches.value_of("from-file") {
+                Some(commands::install::LocalSource::File(path.into()))
+            } else {
+                sub_matches
+                    .value_of("from-dir")
+                    .map(|path| commands::install::LocalSource::Directory(path.into()))
+            };
+            let mut configure_opts: Vec<String> = match sub_matches.values_of("configure_opts") {
+                Some(opts) => opts.map(|opt| opt.to_string()).collect(),
+                None => Vec::new(),
+            };
+            // A local source has no version argument, so options after `--` are passed to
+            // configure. A version is rejected by `Install` instead.
+            let version = match (&local_source, sub_matches.value_of("version")) {
+                (Some(_), Some(opt)) if opt.starts_with('-') => {
+                    configure_opts.insert(0, opt.to_string());
+                    None
+                }
+                (_, version) => version.map(|version| {
                     input_version::InputVersion::from_str(version).expect("invalid version")
                 }),
-                configure_opts: match sub_matches.values_of("configure_opts") {
-                    Some(opts) => opts.map(|opt| opt.to_string()).collect(),
-                    None => Vec::new(),
-                },
+            };
+            commands::install::Install {
+                version,
+                local_source,
+                name: sub_matches
+                    .value_of("as")
+                    .map(|name| version::Version::from_str(name).expect("invalid version")),
+                configure_opts,
+                skip_checksum: sub_matches.is_present("skip-checksum"),
+                verbose: sub_matches.is_present("verbose"),
+                include_prerelease: sub_matches.is_present("include-prerelease"),
             }
             .call(&config);
         }
@@ -73,6 +163,26 @@ fn main() {
             }
             .call(&config);
         }
+        ("logs", Some(sub_matches)) => {
+            commands::logs::Logs {
+                version: input_version::InputVersion::from_str(
+                    sub_matches.value_of("version").unwrap(),
+                )
+                .expect("invalid version"),
+            }
+            .call(&config);
+        }
+        ("cache", Some(sub_matches)) => {
+            commands::cache::Cache {
+                command: match sub_matches.subcommand_name() {
+                    Some("list") => commands::cache::CacheCommand::List,
+                    Some("clean") => commands::cache::CacheCommand::Clean,
+                    Some("prune") => commands::cache::CacheCommand::Prune,
+                    _ => unreachable!(),
+                },
+            }
+            .call(&config);
+        }
         ("completions", Some(sub_matches)) => {
             commands::completions::Completions {
                 shell: sub_matches
@@ -0,0 +1,139 @@
+use crate::build_log::{BuildObserver, Stream};
+use crate::config::FrumConfig;
+use crate::log::LogLevel;
+use crate::outln;
+use colored::Colorize;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Reports the progress of a build phase. By default a spinner line with the
+/// elapsed time and the number of compiled files is redrawn on a terminal. In
+/// verbose mode the build output is streamed instead.
+pub struct Progress<'a> {
+    config: &'a FrumConfig,
+    phase: String,
+    verbose: bool,
+    interactive: bool,
+    started: Instant,
+    last_drawn: Option<Instant>,
+    compiled: usize,
+    frame: usize,
+}
+
+impl<'a> Progress<'a> {
+    pub fn start(config: &'a FrumConfig, phase: &str, verbose: bool) -> Self {
+        let mut progress = Self {
+            config,
+            phase: phase.to_string(),
+            verbose,
+            interactive: !verbose
+                && config.log_level.is_writable(&LogLevel::Info)
+                && std::io::stdout().is_terminal(),
+            started: Instant::now(),
+            last_drawn: None,
+            compiled: 0,
+            frame: 0,
+        };
+        if verbose {
+            outln!(config#Info, "{} {}", "-->".green(), phase);
+        }
+        progress.draw();
+        progress
+    }
+
+    pub fn finish(self) {
+        let config = self.config;
+        if self.interactive {
+            print!("\r\x1b[2K");
+        }
+        let mut summary = format_duration(self.started.elapsed());
+        if self.compiled > 0 {
+            summary.push_str(&format!(", {} files compiled", self.compiled));
+        }
+        outln!(config#Info, "    {} {}", self.phase, format!("({})", summary).dimmed());
+    }
+
+    fn draw(&mut self) {
+        if !self.interactive {
+            return;
+        }
+        if let Some(last_drawn) = self.last_drawn {
+            if last_drawn.elapsed() < REDRAW_INTERVAL {
+                return;
+            }
+        }
+        self.last_drawn = Some(Instant::now());
+        self.frame = (self.frame + 1) % SPINNER.len();
+
+        let mut status = format!(
+            "{} {} {}",
+            SPINNER[self.frame].to_string().green(),
+            self.phase,
+            format_duration(self.started.elapsed()).dimmed()
+        );
+        if self.compiled > 0 {
+            status.push_str(&format!(" ({} files compiled)", self.compiled));
+        }
+        let mut stdout = std::io::stdout();
+        write!(stdout, "\r\x1b[2K{}", status).ok();
+        stdout.flush().ok();
+    }
+}
+
+impl BuildObserver for Progress<'_> {
+    fn line(&mut self, stream: Stream, line: &str) {
+        if self.verbose {
+            let config = self.config;
+            match stream {
+                Stream::Stdout => outln!(config#Info, "{}", line),
+                Stream::Stderr => {
+                    if config.log_level.is_writable(&LogLevel::Info) {
+                        eprintln!("{}", line);
+                    }
+                }
+            }
+            return;
+        }
+        if line.trim_start().starts_with("compiling ") {
+            self.compiled += 1;
+        }
+        self.draw();
+    }
+
+    fn tick(&mut self) {
+        self.draw();
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+    }
+
+    #[test]
+    fn test_count_compiled_files() {
+        let config = FrumConfig::default();
+        let mut progress = Progress::start(&config, "make", false);
+        progress.line(Stream::Stdout, "compiling array.c");
+        progress.line(Stream::Stdout, "linking miniruby");
+        progress.line(Stream::Stdout, "    compiling string.c");
+        assert_eq!(progress.compiled, 2);
+    }
+}
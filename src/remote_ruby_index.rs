@@ -1,5 +1,14 @@
+use crate::checksum::Sha256Reader;
+use crate::config::FrumConfig;
 use crate::version::Version;
+use log::debug;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use thiserror::Error;
 
+#[derive(Serialize, Deserialize)]
 pub struct IndexedRubyVersion {
     pub version: Version,
     pub url: String,
@@ -8,44 +17,378 @@ pub struct IndexedRubyVersion {
     pub sha521: String,
 }
 
-pub fn list(base_url: &reqwest::Url) -> Result<Vec<IndexedRubyVersion>, reqwest::Error> {
-    let value =
-        reqwest::blocking::get(format!("{}/index.txt", base_url.as_str()).as_str())?.text()?;
-    let re = regex::Regex::new(r"(\S+)\s+(\S+)\s+(\S+)\s+(\S+)\s+(\S+)").unwrap();
-    let mut versions = vec![];
-    for (index, line) in value.split('\n').enumerate() {
-        if line.is_empty() || index == 0 {
-            continue;
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("The index of {url} isn't cached yet. Run frum without --offline to fetch it.")]
+    NotCached { url: String },
+    #[error("{url} is not a Ruby index. It starts with {line:?}")]
+    NotAnIndex { url: String, line: String },
+    #[error("Malformed line {line_number} in {url}: {line:?}")]
+    MalformedLine {
+        url: String,
+        line_number: usize,
+        line: String,
+    },
+}
+
+/// The parsed `index.txt` of a mirror stored at `<cache_dir>/index/<sha256 of url>.json`.
+#[derive(Serialize, Deserialize)]
+struct CachedIndex {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: i64,
+    versions: Vec<IndexedRubyVersion>,
+}
+
+impl CachedIndex {
+    fn is_fresh(&self, config: &FrumConfig) -> bool {
+        let age = chrono::Utc::now().timestamp() - self.fetched_at;
+        age >= 0 && (age as u64) < config.index_ttl.as_secs()
+    }
+}
+
+/// Lists the versions in the index of the configured mirror. The index is cached on
+/// disk and revalidated once it is older than `config.index_ttl`. In offline mode only
+/// the cached index is used.
+pub fn list(config: &FrumConfig) -> Result<Vec<IndexedRubyVersion>, Error> {
+    let url = index_url(&config.ruby_build_mirror);
+    let cached = read_cache(config, &url);
+    if config.offline {
+        return cached
+            .map(|cached| cached.versions)
+            .ok_or(Error::NotCached { url });
+    }
+    match cached {
+        Some(cached) if cached.is_fresh(config) => {
+            debug!("use the cached index of {}", url);
+            Ok(cached.versions)
+        }
+        cached => fetch(config, url, cached),
+    }
+}
+
+/// Returns the cached index of the configured mirror, if any.
+pub fn cached(config: &FrumConfig) -> Option<Vec<IndexedRubyVersion>> {
+    read_cache(config, &index_url(&config.ruby_build_mirror)).map(|cached| cached.versions)
+}
+
+fn fetch(
+    config: &FrumConfig,
+    url: String,
+    cached: Option<CachedIndex>,
+) -> Result<Vec<IndexedRubyVersion>, Error> {
+    let client = reqwest::blocking::Client::new();
+    let mut request = client.get(&url);
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
         }
-        let cap = re.captures(line).unwrap();
-        if cap
-            .get(1)
-            .map_or("".to_string(), |m| m.as_str().to_string())
-            .starts_with("ruby-0")
-        {
+    }
+    let response = match request.send() {
+        Ok(response) => response,
+        Err(err) => match cached {
+            Some(cached) if err.is_connect() || err.is_timeout() => {
+                debug!(
+                    "can't revalidate the index of {}, use the cached one: {}",
+                    url, err
+                );
+                return Ok(cached.versions);
+            }
+            _ => return Err(err.into()),
+        },
+    };
+
+    let mut index = match cached {
+        Some(cached) if response.status() == StatusCode::NOT_MODIFIED => {
+            debug!("the cached index of {} is up to date", url);
+            cached
+        }
+        _ => {
+            let response = response.error_for_status()?;
+            let header = |name| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string())
+            };
+            let etag = header(ETAG);
+            let last_modified = header(LAST_MODIFIED);
+            let versions = parse(&url, &response.text()?)?;
+            CachedIndex {
+                url,
+                etag,
+                last_modified,
+                fetched_at: 0,
+                versions,
+            }
+        }
+    };
+    index.fetched_at = chrono::Utc::now().timestamp();
+    if let Err(err) = write_cache(config, &index) {
+        debug!("can't cache the index of {}: {}", index.url, err);
+    }
+    Ok(index.versions)
+}
+
+fn index_url(base_url: &reqwest::Url) -> String {
+    format!("{}/index.txt", base_url.as_str())
+}
+
+fn cache_path(config: &FrumConfig, url: &str) -> PathBuf {
+    let key = Sha256Reader::new(url.as_bytes())
+        .finish()
+        .expect("Can't hash a string");
+    config
+        .cache_dir()
+        .join("index")
+        .join(format!("{}.json", key))
+}
+
+fn read_cache(config: &FrumConfig, url: &str) -> Option<CachedIndex> {
+    let contents = std::fs::read(cache_path(config, url)).ok()?;
+    match serde_json::from_slice::<CachedIndex>(&contents) {
+        Ok(cached) if cached.url == url => Some(cached),
+        Ok(_) => None,
+        Err(err) => {
+            debug!("ignore the corrupted index cache of {}: {}", url, err);
+            None
+        }
+    }
+}
+
+fn write_cache(config: &FrumConfig, index: &CachedIndex) -> std::io::Result<()> {
+    let path = cache_path(config, &index.url);
+    let dir = path.parent().expect("must have parent");
+    std::fs::create_dir_all(dir)?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    serde_json::to_writer(&mut file, index)?;
+    file.persist(&path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// The columns `index.txt` starts with.
+const INDEX_HEADER: [&str; 5] = ["name", "url", "sha1", "sha256", "sha512"];
+
+/// Parses the `index.txt` fetched from `url`. Entries that aren't Ruby releases
+/// (e.g. `ruby-0.49`) are skipped, but a line that doesn't have the five columns
+/// or a SHA-256 digest means we didn't get an index at all.
+fn parse(url: &str, value: &str) -> Result<Vec<IndexedRubyVersion>, Error> {
+    let mut lines = value.lines().enumerate();
+    let header = lines
+        .next()
+        .map(|(_, line)| line.split_whitespace().collect::<Vec<_>>())
+        .unwrap_or_default();
+    if header != INDEX_HEADER {
+        return Err(Error::NotAnIndex {
+            url: url.to_string(),
+            line: value.lines().next().unwrap_or_default().to_string(),
+        });
+    }
+
+    let mut versions = vec![];
+    for (index, line) in lines {
+        if line.trim().is_empty() {
             continue;
         }
+        let columns = line.split_whitespace().collect::<Vec<_>>();
+        // The sha256 ends up in paths of the download cache, so it must be a digest.
+        let (name, url_column, sha1, sha256, sha512) = match columns.as_slice() {
+            [name, url, sha1, sha256, sha512] if is_hex_digest(sha256, 64) => {
+                (*name, *url, *sha1, *sha256, *sha512)
+            }
+            _ => {
+                return Err(Error::MalformedLine {
+                    url: url.to_string(),
+                    line_number: index + 1,
+                    line: line.to_string(),
+                })
+            }
+        };
+        let version = match name.strip_prefix("ruby-").map(Version::parse) {
+            Some(Ok(version @ Version::Ruby(_))) if !name.starts_with("ruby-0") => version,
+            _ => {
+                debug!("skip {} in the index of {}", name, url);
+                continue;
+            }
+        };
         versions.push(IndexedRubyVersion {
-            version: match Version::parse(
-                cap.get(1)
-                    .map_or("".to_string(), |m| m.as_str().to_string()),
-            ) {
-                Ok(version) => version,
-                Err(_) => continue,
-            },
-            url: cap
-                .get(2)
-                .map_or("".to_string(), |m| m.as_str().to_string()),
-            sha1: cap
-                .get(3)
-                .map_or("".to_string(), |m| m.as_str().to_string()),
-            sha256: cap
-                .get(4)
-                .map_or("".to_string(), |m| m.as_str().to_string()),
-            sha521: cap
-                .get(5)
-                .map_or("".to_string(), |m| m.as_str().to_string()),
+            version,
+            url: url_column.to_string(),
+            sha1: sha1.to_string(),
+            sha256: sha256.to_string(),
+            sha521: sha512.to_string(),
         })
     }
     Ok(versions)
 }
+
+fn is_hex_digest(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn cached_index(config: &FrumConfig, fetched_at: i64) -> CachedIndex {
+        CachedIndex {
+            url: index_url(&config.ruby_build_mirror),
+            etag: Some("\"etag\"".to_string()),
+            last_modified: None,
+            fetched_at,
+            versions: parse(
+                "https://cache.ruby-lang.org/pub/ruby/index.txt",
+                &format!(
+                    "name\turl\tsha1\tsha256\tsha512\n\
+                     ruby-2.7.0\thttps://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.0.tar.xz\ta\t{}\tc\n",
+                    "0".repeat(64)
+                ),
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_offline_uses_cached_index() {
+        let mut config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        assert!(matches!(list(&config), Err(Error::NotCached { .. })));
+
+        write_cache(&config, &cached_index(&config, 0)).unwrap();
+        let versions = list(&config).unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, Version::parse("2.7.0").unwrap());
+
+        config.ruby_build_mirror = reqwest::Url::parse("https://example.com/ruby").unwrap();
+        assert!(matches!(list(&config), Err(Error::NotCached { .. })));
+    }
+
+    #[test]
+    fn test_cached_index_expires() {
+        let config = FrumConfig {
+            index_ttl: std::time::Duration::from_secs(60),
+            ..Default::default()
+        };
+        let now = chrono::Utc::now().timestamp();
+        assert!(cached_index(&config, now - 30).is_fresh(&config));
+        assert!(!cached_index(&config, now - 90).is_fresh(&config));
+    }
+
+    #[test]
+    fn test_unreachable_mirror_falls_back_to_cached_index() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ruby_build_mirror: reqwest::Url::parse("http://127.0.0.1:9").unwrap(),
+            ..Default::default()
+        };
+        assert!(matches!(list(&config), Err(Error::Http(_))));
+
+        write_cache(&config, &cached_index(&config, 0)).unwrap();
+        assert_eq!(list(&config).unwrap().len(), 1);
+    }
+
+    const INDEX_URL: &str = "https://cache.ruby-lang.org/pub/ruby/index.txt";
+
+    #[test]
+    fn test_parse_index() {
+        let versions = parse(
+            INDEX_URL,
+            include_str!("../tests/fixtures/index/cache.ruby-lang.org.txt"),
+        )
+        .unwrap();
+        let names = versions
+            .iter()
+            .map(|x| x.version.to_string())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![
+                "1.8.7-p374",
+                "1.9.3-p0",
+                "2.0.0-preview1",
+                "2.7.0",
+                "2.7.0-rc1",
+                "3.3.0",
+                "3.3.0-preview1"
+            ]
+        );
+        let entry = versions
+            .iter()
+            .find(|x| x.url.ends_with("/ruby-3.3.0.tar.xz"))
+            .unwrap();
+        assert_eq!(entry.version, Version::parse("3.3.0").unwrap());
+        assert_eq!(entry.sha256.len(), 64);
+        assert_eq!(entry.sha521.len(), 128);
+    }
+
+    #[test]
+    fn test_parse_index_with_crlf() {
+        let versions = parse(INDEX_URL, include_str!("../tests/fixtures/index/crlf.txt")).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert!(versions.iter().all(|x| !x.sha521.ends_with('\r')));
+    }
+
+    #[test]
+    fn test_parse_html_error_page() {
+        let result = parse(
+            INDEX_URL,
+            include_str!("../tests/fixtures/index/proxy_error.html"),
+        );
+        match result {
+            Err(Error::NotAnIndex { url, line }) => {
+                assert_eq!(url, INDEX_URL);
+                assert_eq!(line, "<!DOCTYPE html>");
+            }
+            _ => panic!("expected NotAnIndex"),
+        }
+        assert!(matches!(
+            parse(INDEX_URL, ""),
+            Err(Error::NotAnIndex { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_truncated_index() {
+        let result = parse(
+            INDEX_URL,
+            include_str!("../tests/fixtures/index/truncated.txt"),
+        );
+        match result {
+            Err(Error::MalformedLine {
+                line_number, line, ..
+            }) => {
+                assert_eq!(line_number, 4);
+                assert!(line.starts_with("ruby-1.8.7-p374\t"));
+            }
+            _ => panic!("expected MalformedLine"),
+        }
+    }
+
+    #[test]
+    fn test_parse_index_with_invalid_sha256() {
+        let result = parse(
+            INDEX_URL,
+            include_str!("../tests/fixtures/index/invalid_sha256.txt"),
+        );
+        match result {
+            Err(Error::MalformedLine {
+                line_number, line, ..
+            }) => {
+                assert_eq!(line_number, 3);
+                assert!(line.starts_with("ruby-3.3.0\t"));
+            }
+            _ => panic!("expected MalformedLine"),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::version::{RubyVersion, Version};
+use std::cmp::Ordering;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A Bundler style version requirement like `~> 3.2.0` or `>= 3.0, < 3.3`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub constraints: Vec<Constraint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub operator: Operator,
+    pub numbers: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    NotEq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// `~>`, which allows the last given number to increase.
+    Pessimistic,
+}
+
+#[derive(Error, Debug)]
+#[error("{requirement:?} is not a version requirement")]
+pub struct ParseError {
+    requirement: String,
+}
+
+impl Requirement {
+    /// Whether a CRuby version satisfies every constraint. Prereleases sort before
+    /// their release, so `>= 3.3.0` doesn't match `3.3.0-preview1`.
+    pub fn matches(&self, version: &Version) -> bool {
+        match version {
+            Version::Ruby(version) => self
+                .constraints
+                .iter()
+                .all(|constraint| constraint.matches(version)),
+            _ => false,
+        }
+    }
+}
+
+impl Constraint {
+    fn matches(&self, version: &RubyVersion) -> bool {
+        let ordering = compare(version, &self.numbers);
+        match self.operator {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::NotEq => ordering != Ordering::Equal,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Ge => ordering != Ordering::Less,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Le => ordering != Ordering::Greater,
+            Operator::Pessimistic => {
+                ordering != Ordering::Less
+                    && compare(version, &bump(&self.numbers)) == Ordering::Less
+            }
+        }
+    }
+}
+
+/// Compares a version with the numbers of a constraint, padding the shorter one with zeros.
+fn compare(version: &RubyVersion, numbers: &[u64]) -> Ordering {
+    let version_numbers = [version.major, version.minor, version.patch];
+    let len = numbers.len().max(version_numbers.len());
+    let number = |numbers: &[u64], index: usize| numbers.get(index).copied().unwrap_or(0);
+    (0..len)
+        .map(|index| number(&version_numbers, index).cmp(&number(numbers, index)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(if version.is_prerelease() {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        })
+}
+
+/// The exclusive upper bound of `~>`: `3.2.0` becomes `3.3` and `3.2` becomes `4`.
+fn bump(numbers: &[u64]) -> Vec<u64> {
+    let mut bumped = numbers[..numbers.len().saturating_sub(1).max(1)].to_vec();
+    if let Some(last) = bumped.last_mut() {
+        *last += 1;
+    }
+    bumped
+}
+
+impl FromStr for Constraint {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError {
+            requirement: s.to_string(),
+        };
+        let s = s.trim();
+        let (operator, number) = [
+            ("~>", Operator::Pessimistic),
+            (">=", Operator::Ge),
+            ("<=", Operator::Le),
+            ("!=", Operator::NotEq),
+            ("=", Operator::Eq),
+            (">", Operator::Gt),
+            ("<", Operator::Lt),
+        ]
+        .iter()
+        .find_map(|(prefix, operator)| s.strip_prefix(prefix).map(|rest| (*operator, rest)))
+        .unwrap_or((Operator::Eq, s));
+        let numbers = number
+            .trim()
+            .split('.')
+            .map(|number| number.parse::<u64>().map_err(|_| error()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { operator, numbers })
+    }
+}
+
+impl FromStr for Requirement {
+    type Err = ParseError;
+
+    /// Parses constraints separated by commas or spaces like `>= 3.0, < 3.3` or
+    /// `>=3.1 <3.3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError {
+            requirement: s.to_string(),
+        };
+        let mut constraints = vec![];
+        let mut operator = None;
+        for token in s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            if token.chars().all(|c| "~<>=!".contains(c)) {
+                if operator.replace(token).is_some() {
+                    return Err(error());
+                }
+                continue;
+            }
+            let constraint = match operator.take() {
+                Some(operator) => format!("{}{}", operator, token),
+                None => token.to_string(),
+            };
+            constraints.push(constraint.parse::<Constraint>().map_err(|_| error())?);
+        }
+        if operator.is_some() || constraints.is_empty() {
+            return Err(error());
+        }
+        Ok(Self { constraints })
+    }
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::NotEq => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Pessimistic => "~>",
+        })
+    }
+}
+
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let numbers = self
+            .numbers
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(".");
+        write!(f, "{} {}", self.operator, numbers)
+    }
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let constraints = self
+            .constraints
+            .iter()
+            .map(Constraint::to_string)
+            .collect::<Vec<_>>();
+        f.write_str(&constraints.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: &str, version: &str) -> bool {
+        Requirement::from_str(requirement)
+            .unwrap()
+            .matches(&Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn test_requirement_operators() {
+        assert!(matches("~> 3.2.0", "3.2.0"));
+        assert!(matches("~> 3.2.0", "3.2.9"));
+        assert!(!matches("~> 3.2.0", "3.3.0"));
+        assert!(matches("~> 3.2", "3.9.0"));
+        assert!(!matches("~> 3.2", "4.0.0"));
+        assert!(!matches("~> 3.2", "3.1.4"));
+        assert!(matches(">= 3.0, < 3.3", "3.2.2"));
+        assert!(!matches(">= 3.0, < 3.3", "3.3.0"));
+        assert!(matches("< 3.3", "3.3.0-preview1"));
+        assert!(!matches(">= 3.3.0", "3.3.0-preview1"));
+        assert!(matches("3.2", "3.2.0"));
+        assert!(matches("= 3.2.2", "3.2.2-p53"));
+        assert!(matches("!= 3.2.2", "3.2.1"));
+        assert!(matches("> 3.2", "3.2.1"));
+        assert!(matches("<= 3.2", "3.2.0"));
+        assert!(!matches(">= 3.0", "jruby-9.4.5.0"));
+    }
+
+    #[test]
+    fn test_parse_requirement() {
+        assert_eq!(
+            Requirement::from_str(">=3.0,<3.3").unwrap().to_string(),
+            ">= 3.0, < 3.3"
+        );
+        assert_eq!(
+            Requirement::from_str("~> 3.2.0").unwrap().to_string(),
+            "~> 3.2.0"
+        );
+        assert_eq!(
+            Requirement::from_str(">=3.1 <3.3").unwrap().to_string(),
+            ">= 3.1, < 3.3"
+        );
+        assert!(Requirement::from_str("~> 3.x").is_err());
+        assert!(Requirement::from_str(">= 3.1 <").is_err());
+        assert!(Requirement::from_str("").is_err());
+    }
+}
@@ -27,7 +27,24 @@ impl Shell for Bash {
         .into()
     }
 
-    fn as_clap_shell(&self) -> clap::Shell {
-        clap::Shell::Bash
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc::indoc!(
+            r#"
+                __frum_hook() {
+                    local previous_exit_status=$?
+                    frum --log-level quiet local --if-changed
+                    return $previous_exit_status
+                }
+
+                if [[ "${PROMPT_COMMAND[*]:-}" != *"__frum_hook"* ]]; then
+                    PROMPT_COMMAND="__frum_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+                fi
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        Some(clap::Shell::Bash)
     }
 }
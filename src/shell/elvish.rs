@@ -0,0 +1,42 @@
+use crate::shell::Shell;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Elvish;
+
+impl Shell for Elvish {
+    fn path(&self, path: &Path) -> String {
+        format!("set paths = [{:?} $@paths]", path.to_str().unwrap())
+    }
+
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("set-env {} {:?}", name, value)
+    }
+
+    fn use_on_cd(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                set after-chdir = [$@after-chdir {|_|
+                    try { frum --log-level quiet local } catch { }
+                }]
+            "#
+        )
+        .into()
+    }
+
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                set edit:before-readline = [$@edit:before-readline {
+                    try { frum --log-level quiet local --if-changed } catch { }
+                }]
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        Some(clap::Shell::Elvish)
+    }
+}
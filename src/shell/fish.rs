@@ -26,7 +26,19 @@ impl Shell for Fish {
         .into()
     }
 
-    fn as_clap_shell(&self) -> clap::Shell {
-        clap::Shell::Fish
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                function _frum_autoload_hook --on-variable PWD --on-event fish_prompt --description 'Change Ruby version before the prompt'
+                    status --is-command-substitution; and return
+                    frum --log-level quiet local --if-changed
+                end
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        Some(clap::Shell::Fish)
     }
 }
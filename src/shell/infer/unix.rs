@@ -1,6 +1,6 @@
 #![cfg(unix)]
 
-use crate::shell::{Bash, Fish, PowerShell, Shell, Zsh};
+use crate::shell::{shell_from_name, Shell};
 use log::debug;
 use std::io::{Error, ErrorKind};
 
@@ -25,12 +25,9 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
             .last()
             .expect("Can't read file name of process tree");
 
-        match binary {
-            "sh" | "bash" => return Some(Box::from(Bash)),
-            "zsh" => return Some(Box::from(Zsh)),
-            "fish" => return Some(Box::from(Fish)),
-            "pwsh" => return Some(Box::from(PowerShell)),
-            cmd_name => debug!("binary is not a supported shell: {:?}", cmd_name),
+        match shell_from_name(binary) {
+            Some(shell) => return Some(shell),
+            None => debug!("binary is not a supported shell: {:?}", binary),
         };
 
         pid = process_info.parent_pid;
@@ -40,6 +37,32 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
     None
 }
 
+#[cfg(target_os = "linux")]
+fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    parse_stat(&stat).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid /proc/{}/stat: {:?}", pid, stat),
+        )
+    })
+}
+
+/// Parses `/proc/<pid>/stat`, which is `<pid> (<comm>) <state> <ppid> ...`. The command
+/// may contain spaces and parentheses, so it ends at the last `)`.
+#[cfg(target_os = "linux")]
+fn parse_stat(stat: &str) -> Option<ProcessInfo> {
+    let start = stat.find('(')?;
+    let end = stat.rfind(')')?;
+    let command = stat.get(start + 1..end)?;
+    let ppid = stat[end + 1..].split_whitespace().nth(1)?;
+    Some(ProcessInfo {
+        parent_pid: ppid.parse().ok().filter(|ppid| *ppid != 0),
+        command: command.into(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
 fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
     use std::io::{BufRead, BufReader};
     use std::process::Command;
@@ -95,4 +118,16 @@ mod tests {
         let parent_pid = process_info.ok().and_then(|x| x.parent_pid);
         assert_eq!(parent_pid, Some(std::process::id()));
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_stat() {
+        let process_info =
+            super::parse_stat("4242 (tmux: server (1)) S 17 4242 4242 0 -1").unwrap();
+        assert_eq!(process_info.command, "tmux: server (1)");
+        assert_eq!(process_info.parent_pid, Some(17));
+        let process_info = super::parse_stat("1 (init) S 0 1 1 0 -1").unwrap();
+        assert_eq!(process_info.parent_pid, None);
+        assert!(super::parse_stat("garbage").is_none());
+    }
 }
@@ -1,6 +1,6 @@
 #![cfg(windows)]
 
-use crate::shell::{Bash, PowerShell, Shell, WindowsCommand};
+use crate::shell::{Bash, Elvish, Nushell, PowerShell, Shell, WindowsCommand};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -33,6 +33,12 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
                 | Some("pwsh.EXE") => {
                     return Some(Box::from(PowerShell));
                 }
+                Some("nu.exe") | Some("nu.EXE") => {
+                    return Some(Box::from(Nushell));
+                }
+                Some("elvish.exe") | Some("elvish.EXE") => {
+                    return Some(Box::from(Elvish));
+                }
                 _ => (),
             }
         }
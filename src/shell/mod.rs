@@ -1,43 +1,74 @@
 pub mod bash;
+pub mod elvish;
 pub mod fish;
 pub mod infer;
+pub mod nushell;
 pub mod powershell;
 pub mod windows_command;
+pub mod xonsh;
 pub mod zsh;
 
 use std::fmt::Debug;
 use std::path::Path;
 
 pub use bash::Bash;
+pub use elvish::Elvish;
 pub use fish::Fish;
+pub use nushell::Nushell;
 pub use powershell::PowerShell;
 pub use windows_command::WindowsCommand;
+pub use xonsh::Xonsh;
 pub use zsh::Zsh;
 pub trait Shell: Debug {
     fn path(&self, path: &Path) -> String;
     fn set_env_var(&self, name: &str, value: &str) -> String;
     fn use_on_cd(&self, config: &crate::config::FrumConfig) -> String;
-    fn as_clap_shell(&self) -> clap::Shell;
+    /// Switches versions before every prompt instead of wrapping `cd`.
+    fn use_on_prompt(&self, config: &crate::config::FrumConfig) -> String {
+        self.use_on_cd(config)
+    }
+    /// The shell to generate completions for, if clap supports it.
+    fn as_clap_shell(&self) -> Option<clap::Shell>;
 }
 
 #[cfg(windows)]
-pub const AVAILABLE_SHELLS: &[&str; 5] = &["cmd", "powershell", "bash", "zsh", "fish"];
+pub const AVAILABLE_SHELLS: &[&str; 7] =
+    &["cmd", "powershell", "bash", "zsh", "fish", "nu", "elvish"];
 
 #[cfg(unix)]
-pub const AVAILABLE_SHELLS: &[&str; 4] = &["bash", "zsh", "fish", "powershell"];
+pub const AVAILABLE_SHELLS: &[&str; 7] =
+    &["bash", "zsh", "fish", "powershell", "nu", "elvish", "xonsh"];
 
 #[cfg(windows)]
 pub fn infer_shell() -> Option<Box<dyn Shell>> {
-    self::infer::windows::infer_shell()
+    self::infer::windows::infer_shell().or_else(shell_from_env)
 }
 
 #[cfg(unix)]
 pub fn infer_shell() -> Option<Box<dyn Shell>> {
-    infer::unix::infer_shell()
+    infer::unix::infer_shell().or_else(shell_from_env)
 }
 
-impl From<Box<dyn Shell>> for clap::Shell {
-    fn from(shell: Box<dyn Shell>) -> clap::Shell {
-        shell.as_clap_shell()
+/// Returns the shell of a name in `AVAILABLE_SHELLS` or of its executable, like `pwsh`.
+pub fn shell_from_name(name: &str) -> Option<Box<dyn Shell>> {
+    match name {
+        "sh" | "bash" => Some(Box::from(Bash)),
+        "zsh" => Some(Box::from(Zsh)),
+        "fish" => Some(Box::from(Fish)),
+        "powershell" | "pwsh" => Some(Box::from(PowerShell)),
+        "nu" => Some(Box::from(Nushell)),
+        "elvish" => Some(Box::from(Elvish)),
+        "xonsh" => Some(Box::from(Xonsh)),
+        #[cfg(windows)]
+        "cmd" => Some(Box::from(WindowsCommand)),
+        _ => None,
     }
 }
+
+/// Falls back to the login shell in `$SHELL` when the process tree has no known shell.
+fn shell_from_env() -> Option<Box<dyn Shell>> {
+    let shell = std::env::var_os("SHELL")?;
+    let name = Path::new(&shell).file_stem()?.to_str()?;
+    log::debug!("infer shell from $SHELL: {}", name);
+    shell_from_name(name)
+}
@@ -0,0 +1,45 @@
+use crate::shell::Shell;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Nushell;
+
+impl Shell for Nushell {
+    fn path(&self, path: &Path) -> String {
+        format!(
+            "$env.PATH = ($env.PATH | split row (char esep) | prepend {:?})",
+            path.to_str().unwrap()
+        )
+    }
+
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("$env.{} = {:?}", name, value)
+    }
+
+    fn use_on_cd(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                $env.config.hooks.env_change.PWD = ($env.config.hooks.env_change.PWD? | default [] | append {|before, after|
+                    ^frum --log-level quiet local | complete | ignore
+                })
+            "#
+        )
+        .into()
+    }
+
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                $env.config.hooks.pre_prompt = ($env.config.hooks.pre_prompt? | default [] | append {||
+                    ^frum --log-level quiet local --if-changed | complete | ignore
+                })
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        None
+    }
+}
@@ -24,7 +24,7 @@ impl Shell for PowerShell {
             function Set-LocationWithFrum {
                 param($path)
                 Set-Location $path
-                If (Test-Path .ruby-version) { & frum --log-level quiet local }
+                & frum --log-level quiet local
             }
             Set-Alias cd_with_frum Set-LocationWithFrum -Force
             Remove-Item alias:\cd
@@ -34,7 +34,24 @@ impl Shell for PowerShell {
         .into()
     }
 
-    fn as_clap_shell(&self) -> clap::Shell {
-        clap::Shell::PowerShell
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+            if (-not (Test-Path variable:global:__FrumOriginalPrompt)) {
+                $global:__FrumOriginalPrompt = $function:prompt
+            }
+            function global:prompt {
+                $previousExitCode = $global:LASTEXITCODE
+                & frum --log-level quiet local --if-changed
+                $global:LASTEXITCODE = $previousExitCode
+                & $global:__FrumOriginalPrompt
+            }
+        "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        Some(clap::Shell::PowerShell)
     }
 }
@@ -1,6 +1,4 @@
 @echo off
 cd %1
-if exist .ruby-version (
-    frum local
-)
+frum --log-level quiet local
 @echo on
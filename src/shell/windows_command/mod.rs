@@ -26,8 +26,8 @@ impl Shell for WindowsCommand {
         )
     }
 
-    fn as_clap_shell(&self) -> clap::Shell {
-        panic!("Shell completion is not supported for Windows Command Prompt. Maybe try using PowerShell for a better experience?");
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        None
     }
 }
 
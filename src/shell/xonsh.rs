@@ -0,0 +1,42 @@
+use crate::shell::Shell;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Xonsh;
+
+impl Shell for Xonsh {
+    fn path(&self, path: &Path) -> String {
+        format!("$PATH.insert(0, {:?})", path.to_str().unwrap())
+    }
+
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("${} = {:?}", name, value)
+    }
+
+    fn use_on_cd(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                @events.on_chdir
+                def __frum_on_chdir(olddir, newdir, **kwargs):
+                    $[frum --log-level quiet local]
+            "#
+        )
+        .into()
+    }
+
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc!(
+            r#"
+                @events.on_pre_prompt
+                def __frum_on_pre_prompt(**kwargs):
+                    $[frum --log-level quiet local --if-changed]
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        None
+    }
+}
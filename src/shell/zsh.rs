@@ -28,7 +28,22 @@ impl Shell for Zsh {
         .into()
     }
 
-    fn as_clap_shell(&self) -> clap::Shell {
-        clap::Shell::Zsh
+    fn use_on_prompt(&self, _config: &crate::config::FrumConfig) -> String {
+        indoc::indoc!(
+            r#"
+                autoload -U add-zsh-hook
+                _frum_autoload_hook () {
+                    frum --log-level quiet local --if-changed
+                }
+
+                add-zsh-hook precmd _frum_autoload_hook
+                _frum_autoload_hook
+            "#
+        )
+        .into()
+    }
+
+    fn as_clap_shell(&self) -> Option<clap::Shell> {
+        Some(clap::Shell::Zsh)
     }
 }
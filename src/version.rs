@@ -1,16 +1,242 @@
 use crate::config::FrumConfig;
+use crate::engine::Engine;
 use log::debug;
+use std::cmp::Ordering;
 use std::str::FromStr;
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
 pub enum Version {
-    Semver(semver::Version),
+    Ruby(RubyVersion),
+    Engine(EngineVersion),
     System,
 }
 
-fn start_with_number(s: &str) -> bool {
-    s.chars().next().map(|x| x.is_digit(10)).unwrap_or(false)
+/// A Ruby release number like `3.3.0`, `3.4.0-preview1` or `1.9.3-p551`.
+///
+/// Prereleases sort before their release, patchlevels after it and local builds
+/// like `3.3.0-patched` last, so `2.0.0-rc1 < 2.0.0 < 2.0.0-p648 < 2.0.0-patched`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RubyVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub patchlevel: Option<u64>,
+    /// A prerelease like `preview1` or the name of a local build like `patched`.
+    pub suffix: Option<String>,
+}
+
+/// A release of another Ruby implementation like `jruby-9.4.5.0` or
+/// `truffleruby-23.1.1`. Engines don't share CRuby's three-part numbering.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EngineVersion {
+    pub engine: Engine,
+    pub numbers: Vec<u64>,
+    pub suffix: Option<String>,
+}
+
+#[derive(Error, Debug)]
+#[error("{version:?} is not a Ruby version")]
+pub struct ParseError {
+    version: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(version: S) -> Self {
+        Self {
+            version: version.into(),
+        }
+    }
+}
+
+/// Splits a suffix like `preview10` into `("preview", 10)` so that it sorts after
+/// `preview2`. Prereleases come before the release and local builds after it.
+fn suffix_key(suffix: &Option<String>) -> (u8, &str, u64) {
+    match suffix.as_deref() {
+        None => (1, "", 0),
+        Some(suffix) => {
+            let digits = suffix.len() - suffix.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            let (name, number) = suffix.split_at(suffix.len() - digits);
+            let rank = if is_prerelease_name(name) { 0 } else { 2 };
+            (rank, name, number.parse().unwrap_or(0))
+        }
+    }
+}
+
+/// Only previews, release candidates and dev builds are prereleases.
+fn is_prerelease_name(name: &str) -> bool {
+    matches!(name, "preview" | "rc" | "dev")
+}
+
+fn is_valid_suffix(suffix: &str) -> bool {
+    !suffix.is_empty()
+        && suffix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.')
+}
+
+impl FromStr for RubyVersion {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError {
+            version: s.to_string(),
+        };
+        // `1.9.3p551` is how `ruby -v` prints a patchlevel.
+        let (numbers, suffix) = match s.find(['-', 'p']) {
+            Some(index) if s.as_bytes()[index] == b'-' => (&s[..index], Some(&s[index + 1..])),
+            Some(index) => (&s[..index], Some(&s[index..])),
+            None => (s, None),
+        };
+        let mut numbers = numbers.split('.').map(|number| {
+            if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                number.parse::<u64>().map_err(|_| error())
+            } else {
+                Err(error())
+            }
+        });
+        let (major, minor, patch) = match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(major), Some(minor), Some(patch)) if numbers.next().is_none() => {
+                (major?, minor?, patch?)
+            }
+            _ => return Err(error()),
+        };
+
+        let mut version = Self {
+            major,
+            minor,
+            patch,
+            patchlevel: None,
+            suffix: None,
+        };
+        match suffix {
+            None => {}
+            Some(suffix) if is_patchlevel(suffix) => {
+                version.patchlevel = Some(suffix[1..].parse().map_err(|_| error())?)
+            }
+            Some(suffix) if is_valid_suffix(suffix) => version.suffix = Some(suffix.to_string()),
+            Some(_) => return Err(error()),
+        }
+        Ok(version)
+    }
+}
+
+impl Ord for RubyVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| suffix_key(&self.suffix).cmp(&suffix_key(&other.suffix)))
+            .then_with(|| self.patchlevel.cmp(&other.patchlevel))
+            .then_with(|| self.suffix.cmp(&other.suffix))
+    }
+}
+
+impl PartialOrd for RubyVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl RubyVersion {
+    pub fn is_prerelease(&self) -> bool {
+        suffix_key(&self.suffix).0 == 0
+    }
+}
+
+impl std::fmt::Display for RubyVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(patchlevel) = self.patchlevel {
+            write!(f, "-p{}", patchlevel)?;
+        }
+        if let Some(suffix) = &self.suffix {
+            write!(f, "-{}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+impl EngineVersion {
+    /// The version without the engine name, e.g. `9.4.5.0`.
+    pub fn number(&self) -> String {
+        let mut number = self
+            .numbers
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(".");
+        if let Some(suffix) = &self.suffix {
+            number.push('-');
+            number.push_str(suffix);
+        }
+        number
+    }
+}
+
+impl FromStr for EngineVersion {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError {
+            version: s.to_string(),
+        };
+        let (engine, number) = s.split_once('-').ok_or_else(error)?;
+        let engine = engine.parse::<Engine>().map_err(|_| error())?;
+        let (numbers, suffix) = match number.split_once('-') {
+            Some((numbers, suffix)) if is_valid_suffix(suffix) => {
+                (numbers, Some(suffix.to_string()))
+            }
+            Some(_) => return Err(error()),
+            None => (number, None),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|number| {
+                if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                    number.parse::<u64>().map_err(|_| error())
+                } else {
+                    Err(error())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if numbers.len() < 3 {
+            return Err(error());
+        }
+        Ok(Self {
+            engine,
+            numbers,
+            suffix,
+        })
+    }
+}
+
+impl Ord for EngineVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.engine
+            .cmp(&other.engine)
+            .then_with(|| self.numbers.cmp(&other.numbers))
+            .then_with(|| suffix_key(&self.suffix).cmp(&suffix_key(&other.suffix)))
+            .then_with(|| self.suffix.cmp(&other.suffix))
+    }
+}
+
+impl PartialOrd for EngineVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for EngineVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.engine, self.number())
+    }
+}
+
+fn is_patchlevel(suffix: &str) -> bool {
+    suffix
+        .strip_prefix('p')
+        .map(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
 }
 
 pub fn is_dotfile(dir: &std::fs::DirEntry) -> bool {
@@ -21,16 +247,31 @@ pub fn is_dotfile(dir: &std::fs::DirEntry) -> bool {
 }
 
 impl Version {
-    pub fn parse<S: AsRef<str>>(version_str: S) -> Result<Self, semver::SemVerError> {
-        let lowercased = version_str.as_ref().to_lowercase();
+    pub fn parse<S: AsRef<str>>(version_str: S) -> Result<Self, ParseError> {
+        let lowercased = version_str.as_ref().trim().to_lowercase();
         let trimed_lowercased = lowercased.trim_start_matches("ruby-");
         debug!("{}", trimed_lowercased);
         if lowercased == "system" {
             Ok(Self::System)
-        } else if start_with_number(trimed_lowercased) {
-            Ok(Self::Semver(semver::Version::parse(&trimed_lowercased)?))
+        } else if let Ok(version) = lowercased.parse::<EngineVersion>() {
+            Ok(Self::Engine(version))
         } else {
-            unreachable!()
+            trimed_lowercased
+                .parse()
+                .map(Self::Ruby)
+                .map_err(|_| ParseError {
+                    version: version_str.as_ref().to_string(),
+                })
+        }
+    }
+
+    /// Whether this is a preview or release candidate. Patchlevels like `1.9.3-p0`
+    /// and local builds like `3.3.0-patched` are releases.
+    pub fn is_prerelease(&self) -> bool {
+        match self {
+            Self::Ruby(version) => version.is_prerelease(),
+            Self::Engine(version) => suffix_key(&version.suffix).0 == 0,
+            Self::System => false,
         }
     }
 
@@ -39,26 +280,43 @@ impl Version {
         config: &crate::config::FrumConfig,
     ) -> Option<std::path::PathBuf> {
         match self {
-            v @ Self::Semver(_) => Some(config.versions_dir().join(v.to_string())),
+            v @ Self::Ruby(_) | v @ Self::Engine(_) => {
+                Some(config.versions_dir().join(v.to_string()))
+            }
             Self::System => None,
         }
     }
 }
 
+/// Lists the versions installed in the versions directory.
+pub fn installed_versions(config: &FrumConfig) -> std::io::Result<Vec<Version>> {
+    let mut versions = vec![];
+    for entry in config.versions_dir().read_dir()? {
+        let entry = entry?;
+        if is_dotfile(&entry) {
+            continue;
+        }
+        let file_name = entry.file_name();
+        match file_name.to_str().map(Version::parse) {
+            Some(Ok(version)) => versions.push(version),
+            _ => debug!("skip {:?} in the versions directory", file_name),
+        }
+    }
+    versions.sort();
+    Ok(versions)
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("frum path doesn't exist")]
     EnvNotFound,
     #[error(transparent)]
-    SemverError(#[from] semver::SemVerError),
+    ParseError(#[from] ParseError),
 }
 
 pub fn current_version(config: &FrumConfig) -> Result<Option<Version>, Error> {
-    debug!(
-        "frum_path: {}",
-        config.frum_path.clone().unwrap().to_str().unwrap()
-    );
     let multishell_path = config.frum_path.as_ref().ok_or(Error::EnvNotFound)?;
+    debug!("frum_path: {}", multishell_path.display());
 
     if let Ok(resolved_path) = std::fs::canonicalize(multishell_path) {
         debug!("frum_path: {}", resolved_path.to_str().unwrap());
@@ -67,13 +325,19 @@ pub fn current_version(config: &FrumConfig) -> Result<Option<Version>, Error> {
             .expect("Can't get filename")
             .to_str()
             .expect("Invalid OS string");
-        let version = Version::parse(file_name).map_err(Error::SemverError)?;
+        let version = Version::parse(file_name).map_err(Error::ParseError)?;
         Ok(Some(version))
     } else {
         Ok(None)
     }
 }
 
+/// The version the default alias points to.
+pub fn default_version(config: &FrumConfig) -> Option<Version> {
+    let target = std::fs::read_link(config.default_version_dir()).ok()?;
+    Version::parse(target.file_name()?.to_str()?).ok()
+}
+
 impl<'de> serde::Deserialize<'de> for Version {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -84,27 +348,108 @@ impl<'de> serde::Deserialize<'de> for Version {
     }
 }
 
+impl serde::Serialize for Version {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl std::fmt::Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Semver(semver) => write!(f, "{}", semver),
+            Self::Ruby(version) => write!(f, "{}", version),
+            Self::Engine(version) => write!(f, "{}", version),
             Self::System => write!(f, "system"),
         }
     }
 }
 
 impl FromStr for Version {
-    type Err = semver::SemVerError;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Version, Self::Err> {
         Self::parse(s)
     }
 }
 
-impl PartialEq<semver::Version> for Version {
-    fn eq(&self, other: &semver::Version) -> bool {
-        match self {
-            Self::Semver(v) => v == other,
-            Self::System => false,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ruby_versions() {
+        for (input, expected) in &[
+            ("2.7.0", "2.7.0"),
+            ("ruby-3.3.0", "3.3.0"),
+            ("1.9.3-p551", "1.9.3-p551"),
+            ("1.9.3p551", "1.9.3-p551"),
+            ("3.4.0-preview1", "3.4.0-preview1"),
+            ("3.3.0-RC1", "3.3.0-rc1"),
+            ("system", "system"),
+            ("jruby-9.4.5.0", "jruby-9.4.5.0"),
+            ("truffleruby-23.1.1", "truffleruby-23.1.1"),
+            ("TruffleRuby-24.0.0-preview1", "truffleruby-24.0.0-preview1"),
+            ("mruby-3.2.0", "mruby-3.2.0"),
+        ] {
+            assert_eq!(&Version::parse(input).unwrap().to_string(), expected);
         }
+        for input in &[
+            "",
+            "3",
+            "3.3",
+            "3.3.0.1",
+            "3.3.0-",
+            "3.3.x",
+            "jruby",
+            "jruby-9.4",
+            "rbx-5.0.0",
+            "latest",
+        ] {
+            assert!(
+                Version::parse(input).is_err(),
+                "{:?} should be invalid",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_ruby_version_ordering() {
+        let ordered = [
+            "1.9.3-p551",
+            "2.0.0-preview1",
+            "2.0.0-preview2",
+            "2.0.0-rc1",
+            "2.0.0",
+            "2.0.0-p0",
+            "2.0.0-p247",
+            "2.0.0-p648",
+            "2.0.0-p18446744073709551615",
+            "2.0.0-patched",
+            "2.1.0-preview10",
+            "2.1.0",
+            "2.10.0",
+        ]
+        .iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect::<Vec<_>>();
+        let mut sorted = ordered.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, ordered);
+        assert!(
+            Version::parse("2.1.0-preview10").unwrap() > Version::parse("2.1.0-preview2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_engine_version_ordering() {
+        let version = |s| Version::parse(s).unwrap();
+        assert!(version("jruby-9.4.10.0") > version("jruby-9.4.5.0"));
+        assert!(version("jruby-9.4.5.0") > version("jruby-9.4.5.0-rc1"));
+        assert!(version("jruby-9.4.5.0") > version("3.3.0"));
+        assert!(version("truffleruby-23.1.1") > version("jruby-9.4.5.0"));
     }
 }
@@ -1,54 +1,301 @@
+use crate::config::FrumConfig;
 use crate::input_version::InputVersion;
+use crate::requirement::Requirement;
+use crate::version::{RubyVersion, Version};
 use encoding_rs_io::DecodeReaderBytes;
 use log::{debug, info};
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-const VERSION_FILE: &str = ".ruby-version";
+/// The version file `frum local` writes.
+pub const RUBY_VERSION_FILE: &str = ".ruby-version";
 
-pub fn get_user_version_for_directory(path: PathBuf) -> Option<InputVersion> {
-    let version_file_path = find_up(path, VERSION_FILE)?;
-    info!(
-        "Looking for version file in {}. exists? {}",
-        version_file_path.display(),
-        version_file_path.exists()
-    );
-    if let Some(version) = get_user_version_for_file(version_file_path) {
-        return Some(version);
+/// A kind of file that pins the Ruby version of a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionFile {
+    /// `.ruby-version`, containing just the version.
+    RubyVersion,
+    /// asdf's `.tool-versions`, e.g. `ruby 3.2.2`.
+    ToolVersions,
+    /// mise's `mise.toml` or `.mise.toml`, e.g. `[tools] ruby = "3.3"`.
+    Mise,
+    /// The `RUBY VERSION` section of a `Gemfile.lock`.
+    GemfileLock,
+    /// The `ruby` directive of a `Gemfile`, e.g. `ruby "~> 3.2.0"`.
+    Gemfile,
+}
+
+impl VersionFile {
+    /// The order version files are looked up in unless configured otherwise.
+    pub const DEFAULT: [VersionFile; 5] = [
+        Self::RubyVersion,
+        Self::ToolVersions,
+        Self::Mise,
+        Self::GemfileLock,
+        Self::Gemfile,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RubyVersion => "ruby-version",
+            Self::ToolVersions => "tool-versions",
+            Self::Mise => "mise",
+            Self::GemfileLock => "gemfile-lock",
+            Self::Gemfile => "gemfile",
+        }
+    }
+
+    fn file_names(self) -> &'static [&'static str] {
+        match self {
+            Self::RubyVersion => &[RUBY_VERSION_FILE],
+            Self::ToolVersions => &[".tool-versions"],
+            Self::Mise => &["mise.toml", ".mise.toml"],
+            Self::GemfileLock => &["Gemfile.lock"],
+            Self::Gemfile => &["Gemfile"],
+        }
+    }
+
+    fn parse(self, path: &Path, contents: &str) -> Option<InputVersion> {
+        match self {
+            Self::RubyVersion => InputVersion::from_str(contents.trim()).ok(),
+            Self::ToolVersions => parse_tool_versions(contents),
+            Self::Mise => parse_mise_toml(contents),
+            Self::GemfileLock => parse_gemfile_lock(contents),
+            Self::Gemfile => parse_gemfile(path, contents),
+        }
     }
+}
+
+impl std::fmt::Display for VersionFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for VersionFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_start_matches('.') {
+            "ruby-version" => Ok(Self::RubyVersion),
+            "tool-versions" => Ok(Self::ToolVersions),
+            "mise" | "mise.toml" => Ok(Self::Mise),
+            "gemfile-lock" | "Gemfile.lock" => Ok(Self::GemfileLock),
+            "gemfile" | "Gemfile" => Ok(Self::Gemfile),
+            _ => Err(format!(
+                "Unknown version file {:?}. Possible values: ruby-version, tool-versions, mise, gemfile-lock, gemfile",
+                s
+            )),
+        }
+    }
+}
 
+/// Parses a comma separated list of version files like `tool-versions,ruby-version`.
+pub fn parse_version_files(s: &str) -> Result<Vec<VersionFile>, String> {
+    s.split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(VersionFile::from_str)
+        .collect()
+}
+
+/// A version found in a version file.
+#[derive(Debug)]
+pub struct FoundVersion {
+    pub path: PathBuf,
+    pub kind: VersionFile,
+    pub version: InputVersion,
+}
+
+/// Looks for the version pinned in `path` or its ancestors. The nearest directory
+/// wins; within a directory the configured version files are checked in order.
+pub fn find_version_file(config: &FrumConfig, path: PathBuf) -> Option<FoundVersion> {
+    for dir in each_dir(path) {
+        for version_file in &config.version_files {
+            for file_name in version_file.file_names() {
+                let path = dir.join(file_name);
+                if !path.is_file() {
+                    continue;
+                }
+                info!("Looking for a Ruby version in {}", path.display());
+                match read_version_file(*version_file, &path) {
+                    Some(version) => {
+                        return Some(FoundVersion {
+                            path,
+                            kind: *version_file,
+                            version,
+                        })
+                    }
+                    None => debug!("no Ruby version in {}", path.display()),
+                }
+            }
+        }
+    }
     None
 }
 
-pub fn get_user_version_for_file(path: PathBuf) -> Option<InputVersion> {
+/// Every file `find_version_file` may read for `path`, whether it exists or not.
+/// Checking their metadata is enough to tell that the version can't have changed.
+pub fn version_file_candidates(config: &FrumConfig, path: PathBuf) -> Vec<PathBuf> {
+    each_dir(path)
+        .into_iter()
+        .flat_map(|dir| {
+            config
+                .version_files
+                .iter()
+                .flat_map(|version_file| version_file.file_names())
+                .map(move |file_name| dir.join(file_name))
+        })
+        .collect()
+}
+
+/// The version requested by `$FRUM_VERSION` or the version files of `path`.
+pub fn get_user_version_for_directory(config: &FrumConfig, path: PathBuf) -> Option<InputVersion> {
+    config
+        .env_version
+        .clone()
+        .or_else(|| find_version_file(config, path).map(|found| found.version))
+}
+
+fn read_version_file(version_file: VersionFile, path: &Path) -> Option<InputVersion> {
     let file = std::fs::File::open(path).ok()?;
-    let version = {
+    let contents = {
         let mut reader = DecodeReaderBytes::new(file);
-        let mut version = String::new();
-        reader.read_to_string(&mut version).map(|_| version)
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).map(|_| contents)
     };
 
-    match version {
+    match contents {
         Err(err) => {
             info!("Can't read file: {}", err);
             None
         }
-        Ok(version) => {
-            info!("Found string {:?}  in version file", version);
-            InputVersion::from_str(version.trim()).ok()
+        Ok(contents) => {
+            info!("Found string {:?}  in version file", contents);
+            version_file.parse(path, &contents)
         }
     }
 }
 
-pub fn find_up(search_dir: PathBuf, file_name: &str) -> Option<PathBuf> {
-    for dir in each_dir(search_dir) {
-        let path = dir.join(&file_name);
-        if path.exists() {
-            return Some(path);
+/// Reads the `ruby` line of a `.tool-versions`. asdf allows several versions as
+/// fallbacks, so the first one we understand is used.
+fn parse_tool_versions(contents: &str) -> Option<InputVersion> {
+    contents.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        if words.next() != Some("ruby") {
+            return None;
         }
+        words.find_map(|version| InputVersion::from_str(version).ok())
+    })
+}
+
+/// Reads `[tools] ruby` of a `mise.toml`, which is either a version, a list of
+/// versions or a table with a `version` key.
+fn parse_mise_toml(contents: &str) -> Option<InputVersion> {
+    let value = match contents.parse::<toml::Value>() {
+        Ok(value) => value,
+        Err(err) => {
+            info!("Can't parse mise.toml: {}", err);
+            return None;
+        }
+    };
+    let ruby = value.get("tools")?.get("ruby")?;
+    let version = match ruby {
+        toml::Value::Array(versions) => versions.first()?,
+        toml::Value::Table(table) => table.get("version")?,
+        version => version,
+    };
+    InputVersion::from_str(version.as_str()?.trim()).ok()
+}
+
+/// Reads the `RUBY VERSION` section of a `Gemfile.lock`, e.g. `ruby 3.2.2p53` or
+/// `ruby 2.6.8p001 (jruby 9.3.13.0)`.
+fn parse_gemfile_lock(contents: &str) -> Option<InputVersion> {
+    let line = contents
+        .lines()
+        .skip_while(|line| line.trim() != "RUBY VERSION")
+        .nth(1)?;
+    let mut words = line.split_whitespace();
+    if words.next() != Some("ruby") {
+        return None;
+    }
+    let version = words.next()?;
+    let engine = words.collect::<Vec<_>>().join(" ");
+    if let Some((engine, engine_version)) = engine
+        .strip_prefix('(')
+        .and_then(|engine| engine.strip_suffix(')'))
+        .and_then(|engine| engine.split_once(' '))
+    {
+        if engine != "ruby" {
+            return InputVersion::from_str(&format!("{}-{}", engine, engine_version)).ok();
+        }
+    }
+    // Preview and dev builds have no patchlevel, which Bundler writes as `3.4.0p-1`.
+    let version = version.strip_suffix("p-1").unwrap_or(version);
+    match Version::parse(version).ok()? {
+        Version::Ruby(version) => Some(InputVersion::Full(Version::Ruby(without_patchlevel(
+            version,
+        )))),
+        _ => None,
+    }
+}
+
+/// Since Ruby 2.1 releases aren't named after their patchlevel, so `3.2.2p53` is
+/// installed as `3.2.2`.
+fn without_patchlevel(mut version: RubyVersion) -> RubyVersion {
+    if (version.major, version.minor) >= (2, 1) {
+        version.patchlevel = None;
+    }
+    version
+}
+
+/// Reads the `ruby` directive of a `Gemfile`, which is either a list of requirements
+/// like `ruby ">= 3.0", "< 3.3"` or `ruby file: ".ruby-version"`, optionally with an
+/// `engine:` and `engine_version:`.
+fn parse_gemfile(path: &Path, contents: &str) -> Option<InputVersion> {
+    let argument = regex::Regex::new(r#"(?:(\w+):\s*|:(\w+)\s*=>\s*)?["']([^"']*)["']"#).unwrap();
+    let line = contents.lines().map(str::trim).find(|line| {
+        line.strip_prefix("ruby")
+            .map(|rest| rest.starts_with(|c: char| c.is_whitespace() || c == '('))
+            .unwrap_or(false)
+    })?;
+
+    let mut requirements = vec![];
+    let mut options = std::collections::HashMap::new();
+    for captures in argument.captures_iter(&line["ruby".len()..]) {
+        let value = captures.get(3)?.as_str();
+        match captures.get(1).or_else(|| captures.get(2)) {
+            Some(key) => {
+                options.insert(key.as_str(), value);
+            }
+            None => requirements.push(value),
+        }
+    }
+
+    if let (Some(engine), Some(engine_version)) =
+        (options.get("engine"), options.get("engine_version"))
+    {
+        if *engine != "ruby" {
+            return InputVersion::from_str(&format!("{}-{}", engine, engine_version)).ok();
+        }
+    }
+    if let Some(file) = options.get("file") {
+        let file = path.parent()?.join(file);
+        let version_file = if file.ends_with(".tool-versions") {
+            VersionFile::ToolVersions
+        } else {
+            VersionFile::RubyVersion
+        };
+        return read_version_file(version_file, &file);
+    }
+    match requirements.as_slice() {
+        [] => None,
+        [version] if Version::parse(version).is_ok() => InputVersion::from_str(version).ok(),
+        requirements => Requirement::from_str(&requirements.join(","))
+            .map(InputVersion::Requirement)
+            .ok(),
     }
-    None
 }
 
 fn each_dir(path: PathBuf) -> Vec<PathBuf> {
@@ -64,3 +311,163 @@ fn each_dir(path: PathBuf) -> Vec<PathBuf> {
 
     paths
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn find(config: &FrumConfig, path: &Path) -> Option<String> {
+        get_user_version_for_directory(config, path.to_path_buf()).map(|x| x.to_string())
+    }
+
+    #[test]
+    fn test_parse_version_files() {
+        assert_eq!(
+            VersionFile::ToolVersions
+                .parse(
+                    Path::new(".tool-versions"),
+                    "nodejs 20.0.0\nruby 3.2.2 system # comment\n"
+                )
+                .map(|x| x.to_string()),
+            Some("3.2.2".to_string())
+        );
+        assert_eq!(
+            VersionFile::ToolVersions
+                .parse(Path::new(".tool-versions"), "ruby ref:v3_3_0 3.3.0\n")
+                .map(|x| x.to_string()),
+            Some("3.3.0".to_string())
+        );
+        assert!(VersionFile::ToolVersions
+            .parse(Path::new(".tool-versions"), "# ruby 3.2.2\n")
+            .is_none());
+        for contents in &[
+            "[tools]\nruby = \"3.3\"\n",
+            "[tools]\nruby = [\"3.3\", \"3.2\"]\n",
+            "[tools]\nruby = { version = \"3.3\" }\n",
+        ] {
+            assert_eq!(
+                VersionFile::Mise
+                    .parse(Path::new("mise.toml"), contents)
+                    .map(|x| x.to_string()),
+                Some("3.3.x".to_string())
+            );
+        }
+        assert!(VersionFile::Mise
+            .parse(Path::new("mise.toml"), "[tools]\nnode = \"20\"\n")
+            .is_none());
+        assert!(VersionFile::Mise
+            .parse(Path::new("mise.toml"), "not toml")
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_gemfile_lock() {
+        let parse = |contents| {
+            VersionFile::GemfileLock
+                .parse(Path::new("Gemfile.lock"), contents)
+                .map(|x| x.to_string())
+        };
+        let lock = "GEM\n  specs:\n\nRUBY VERSION\n   ruby 3.2.2p53\n\nBUNDLED WITH\n   2.4.10\n";
+        assert_eq!(parse(lock), Some("3.2.2".to_string()));
+        assert_eq!(
+            parse("RUBY VERSION\n   ruby 2.0.0p648\n"),
+            Some("2.0.0-p648".to_string())
+        );
+        assert_eq!(
+            parse("RUBY VERSION\n   ruby 2.6.8p001 (jruby 9.3.13.0)\n"),
+            Some("jruby-9.3.13.0".to_string())
+        );
+        assert_eq!(
+            parse("RUBY VERSION\n   ruby 3.4.0p-1\n"),
+            Some("3.4.0".to_string())
+        );
+        assert_eq!(parse("GEM\n  specs:\n"), None);
+    }
+
+    #[test]
+    fn test_parse_gemfile() {
+        let project = tempdir().unwrap();
+        let gemfile = project.path().join("Gemfile");
+        let parse = |contents| {
+            VersionFile::Gemfile
+                .parse(&gemfile, contents)
+                .map(|x| x.to_string())
+        };
+        assert_eq!(
+            parse("source \"https://rubygems.org\"\n\nruby \"~> 3.2.0\"\ngem \"rails\"\n"),
+            Some("~> 3.2.0".to_string())
+        );
+        assert_eq!(parse("ruby '3.2.2'\n"), Some("3.2.2".to_string()));
+        assert_eq!(
+            parse("ruby(\">= 3.0\", \"< 3.3\")\n"),
+            Some(">= 3.0, < 3.3".to_string())
+        );
+        assert_eq!(
+            parse("ruby \"3.1.4\", engine: \"jruby\", engine_version: \"9.4.5.0\"\n"),
+            Some("jruby-9.4.5.0".to_string())
+        );
+        assert_eq!(
+            parse("ruby \"3.1.4\", :engine => \"jruby\", :engine_version => \"9.4.5.0\"\n"),
+            Some("jruby-9.4.5.0".to_string())
+        );
+        assert_eq!(parse("gem \"ruby-progressbar\"\n"), None);
+
+        std::fs::write(project.path().join(".ruby-version"), "3.3.0\n").unwrap();
+        assert_eq!(
+            parse("ruby file: \".ruby-version\"\n"),
+            Some("3.3.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_version_file_priority() {
+        let config = FrumConfig::default();
+        let project = tempdir().unwrap();
+        let sub_dir = project.path().join("sub");
+        std::fs::create_dir(&sub_dir).unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.7.0\n").unwrap();
+        std::fs::write(project.path().join(".tool-versions"), "ruby 3.2.2\n").unwrap();
+        std::fs::write(sub_dir.join("mise.toml"), "[tools]\nruby = \"3.3.0\"\n").unwrap();
+
+        assert_eq!(find(&config, project.path()), Some("2.7.0".to_string()));
+        assert_eq!(find(&config, &sub_dir), Some("3.3.0".to_string()));
+
+        let config = FrumConfig {
+            version_files: vec![VersionFile::ToolVersions, VersionFile::RubyVersion],
+            ..Default::default()
+        };
+        assert_eq!(find(&config, project.path()), Some("3.2.2".to_string()));
+        assert_eq!(find(&config, &sub_dir), Some("3.2.2".to_string()));
+    }
+
+    #[test]
+    fn test_env_version_overrides_version_files() {
+        let project = tempdir().unwrap();
+        std::fs::write(project.path().join(".ruby-version"), "2.7.0\n").unwrap();
+        let config = FrumConfig {
+            env_version: None,
+            ..Default::default()
+        };
+        assert_eq!(find(&config, project.path()), Some("2.7.0".to_string()));
+
+        let config = FrumConfig {
+            env_version: Some(InputVersion::from_str("3.2").unwrap()),
+            ..config
+        };
+        assert_eq!(find(&config, project.path()), Some("3.2.x".to_string()));
+        assert_eq!(
+            find(&config, &project.path().join("sub")),
+            Some("3.2.x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_version_file_list() {
+        assert_eq!(
+            parse_version_files("tool-versions, .ruby-version").unwrap(),
+            vec![VersionFile::ToolVersions, VersionFile::RubyVersion]
+        );
+        assert!(parse_version_files("nvmrc").is_err());
+    }
+}
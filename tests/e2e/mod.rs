@@ -1,4 +1,6 @@
+use crate::utils::{cmd_exists, Dir};
 use crate::{e2e_test, eq, eq_re};
+use std::path::Path;
 
 e2e_test!(init, |dir| {
     dir.command().arg("init").output();
@@ -8,9 +10,72 @@ e2e_test!(init, |dir| {
     eq_re!("FRUM_DIR", stdout);
     eq_re!("FRUM_LOGLEVEL", stdout);
     eq_re!("FRUM_RUBY_BUILD_MIRROR", stdout);
+    eq_re!("FRUM_VERSION_FILES", stdout);
     eq_re!("frum --log-level quiet local", stdout);
 });
 
+e2e_test!(init_hook_modes, |dir| {
+    let stdout = dir.command().arg("init").stdout();
+    eq_re!("frum --log-level quiet local --if-changed", stdout);
+    let stdout = dir.command().args(["init", "--hook", "cd"]).stdout();
+    assert!(!stdout.contains("--if-changed"));
+    eq_re!("frum --log-level quiet local", stdout);
+});
+
+e2e_test!(init_explicit_shell, |dir| {
+    let stdout = dir.command().args(["init", "--shell", "fish"]).stdout();
+    eq_re!("set -gx FRUM_DIR", stdout);
+    let stdout = dir.command().args(["init", "--shell", "nu"]).stdout();
+    eq_re!(r"\$env.FRUM_DIR = ", stdout);
+});
+
+/// Sources the output of `frum init` in `shell`, whose `script` prints `FRUM_DIR`,
+/// `FRUM_MULTISHELL_PATH` and the first entry of `PATH` on separate lines.
+fn init_in_shell(dir: &Dir, shell: &str, init: &str, file: &str, script: &str) {
+    if !cmd_exists(shell) {
+        eprintln!("skipping: {} is not installed", shell);
+        return;
+    }
+    let init = dir.shell_command(shell, init).stdout();
+    dir.create(file, &init);
+    let stdout = dir.shell_command(shell, script).stdout();
+    let lines = stdout.lines().collect::<Vec<_>>();
+    eq!(dir.path().to_str().unwrap(), lines[0]);
+    let bin_dir = Path::new(lines[1]).join("bin");
+    eq!(bin_dir.to_str().unwrap(), lines[2]);
+}
+
+e2e_test!(init_nushell, |dir| {
+    init_in_shell(
+        &dir,
+        "nu",
+        "^frum init",
+        "init.nu",
+        "source init.nu; print $env.FRUM_DIR; print $env.FRUM_MULTISHELL_PATH; print ($env.PATH | first)",
+    );
+});
+
+e2e_test!(init_elvish, |dir| {
+    // The prompt hook needs the interactive editor, which `elvish -c` doesn't load.
+    init_in_shell(
+        &dir,
+        "elvish",
+        "frum init --hook cd",
+        "init.elv",
+        "eval (slurp < init.elv); echo $E:FRUM_DIR; echo $E:FRUM_MULTISHELL_PATH; echo $paths[0]",
+    );
+});
+
+e2e_test!(init_xonsh, |dir| {
+    init_in_shell(
+        &dir,
+        "xonsh",
+        "frum init",
+        "init.xsh",
+        "source init.xsh\nprint($FRUM_DIR)\nprint($FRUM_MULTISHELL_PATH)\nprint($PATH[0])",
+    );
+});
+
 e2e_test!(use_installed_version, |dir| {
     dir.command().arg("install").arg("2.7.0").output();
     dir.command().arg("local").arg("2.7.0").output();
@@ -141,6 +141,25 @@ impl Dir {
         }
     }
 
+    /// Creates a command that runs `script` with `shell -c` in this working
+    /// directory, with the frum executable on `PATH`.
+    pub fn shell_command(&self, shell: &str, script: &str) -> TestCommand {
+        let path = env::var_os("PATH").unwrap_or_default();
+        let path =
+            env::join_paths(std::iter::once(self.root.join("..")).chain(env::split_paths(&path)))
+                .unwrap();
+        let mut cmd = Command::new(shell);
+        cmd.arg("-c")
+            .arg(script)
+            .current_dir(&self.dir)
+            .env("PATH", path)
+            .env("FRUM_DIR", self.dir.to_str().unwrap());
+        TestCommand {
+            dir: self.clone(),
+            cmd,
+        }
+    }
+
     pub fn ruby_version(&self) -> String {
         let o = Command::new("ruby")
             .arg("-v")